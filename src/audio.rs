@@ -0,0 +1,439 @@
+//! Sound effects and music, played with rodio (and not ggez audio, so that a missing
+//! audio device does not prevent the game from starting). There are no sound files,
+//! the sounds are synthesized (as in-memory WAV files) when the game starts.
+//!
+//! Each scene has its music (see `Scene::music`), and the music crossfades from one to
+//! the other when scenes change. When there is no audio (the `audio` feature is disabled,
+//! or there is no audio device), the null backend plays nothing but everything
+//! else works the same.
+
+// Without audio, the sounds are not synthesized, as they cannot be played.
+#![cfg_attr(not(feature = "audio"), allow(dead_code))]
+
+use std::cell::RefCell;
+use std::f32::consts::TAU;
+#[cfg(feature = "audio")]
+use std::io::Cursor;
+use std::rc::Rc;
+#[cfg(feature = "audio")]
+use std::sync::Arc;
+use std::time::Duration;
+
+use ggez::GameResult;
+#[cfg(feature = "audio")]
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink};
+
+use crate::settings::Settings;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SoundEffect {
+	CardPickup,
+	Placement,
+	Food,
+	Hit,
+	Flee,
+}
+
+impl SoundEffect {
+	const ALL: [SoundEffect; 5] = [
+		SoundEffect::CardPickup,
+		SoundEffect::Placement,
+		SoundEffect::Food,
+		SoundEffect::Hit,
+		SoundEffect::Flee,
+	];
+
+	fn notes(self) -> Vec<Note> {
+		let note = |start, duration, (from, to), volume, wave| Note {
+			start,
+			duration,
+			from,
+			to,
+			volume,
+			wave,
+		};
+		match self {
+			SoundEffect::CardPickup => vec![note(0.0, 0.08, (660.0, 990.0), 0.35, Wave::Sine)],
+			SoundEffect::Placement => vec![
+				note(0.0, 0.16, (200.0, 90.0), 0.6, Wave::Sine),
+				note(0.0, 0.05, (0.0, 0.0), 0.15, Wave::Noise),
+			],
+			SoundEffect::Food => vec![
+				note(0.0, 0.09, (520.0, 560.0), 0.35, Wave::Triangle),
+				note(0.08, 0.14, (780.0, 840.0), 0.35, Wave::Triangle),
+			],
+			SoundEffect::Hit => vec![
+				note(0.0, 0.12, (0.0, 0.0), 0.4, Wave::Noise),
+				note(0.0, 0.15, (160.0, 70.0), 0.5, Wave::Sine),
+			],
+			SoundEffect::Flee => vec![note(0.0, 0.35, (900.0, 220.0), 0.3, Wave::Triangle)],
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Music {
+	/// The dream map (and the other peaceful scenes).
+	Dream,
+	Battle,
+	/// The defeat screen.
+	Nightmare,
+}
+
+impl Music {
+	const ALL: [Music; 3] = [Music::Dream, Music::Battle, Music::Nightmare];
+
+	/// The tune loops, so its notes all end before it does.
+	fn samples(self) -> Vec<f32> {
+		match self {
+			Music::Dream => tune(
+				&[72, 76, 79, 83, 69, 72, 76, 79, 65, 69, 72, 76, 67, 71, 74, 79],
+				&[48, 45, 41, 43],
+				0.35,
+				Wave::Triangle,
+			),
+			Music::Battle => tune(
+				&[
+					57, 60, 64, 60, 57, 60, 64, 69, 53, 57, 60, 57, 53, 57, 60, 65, 55, 59, 62, 59, 55,
+					59, 62, 67, 52, 56, 59, 56, 52, 56, 59, 64,
+				],
+				&[45, 45, 41, 41, 43, 43, 40, 40],
+				0.18,
+				Wave::Triangle,
+			),
+			Music::Nightmare => tune(
+				&[63, 60, 57, 54, 62, 59, 56, 53, 63, 60, 57, 54, 61, 58, 55, 52],
+				&[51, 50, 51, 49],
+				0.6,
+				Wave::Sine,
+			),
+		}
+	}
+}
+
+const SAMPLE_RATE: u32 = 22050;
+
+#[derive(Clone, Copy)]
+enum Wave {
+	Sine,
+	Triangle,
+	/// The frequency does not matter for noise.
+	Noise,
+}
+
+/// A note that slides from `from` to `to` (in Hz), with a quick attack and then a decay
+/// to silence at the end of its duration (in seconds, like its start).
+struct Note {
+	start: f32,
+	duration: f32,
+	from: f32,
+	to: f32,
+	volume: f32,
+	wave: Wave,
+}
+
+fn frequency(midi_note: i32) -> f32 {
+	440.0 * 2.0f32.powf((midi_note - 69) as f32 / 12.0)
+}
+
+/// A melody (one note per beat) over a bass line (one note per bar of 4 beats).
+fn tune(melody: &[i32], bass: &[i32], beat: f32, melody_wave: Wave) -> Vec<f32> {
+	let mut notes = vec![];
+	for (i, midi_note) in melody.iter().enumerate() {
+		let pitch = frequency(*midi_note);
+		let start = i as f32 * beat;
+		notes.push(Note {
+			start,
+			duration: beat,
+			from: pitch,
+			to: pitch,
+			volume: 0.25,
+			wave: melody_wave,
+		});
+	}
+	for (i, midi_note) in bass.iter().enumerate() {
+		let pitch = frequency(*midi_note);
+		let start = i as f32 * beat * 4.0;
+		notes.push(Note {
+			start,
+			duration: beat * 4.0,
+			from: pitch,
+			to: pitch,
+			volume: 0.2,
+			wave: Wave::Sine,
+		});
+	}
+	render(melody.len() as f32 * beat, &notes)
+}
+
+/// Mixes the notes into samples (from -1 to 1), cutting what goes past `length` seconds.
+fn render(length: f32, notes: &[Note]) -> Vec<f32> {
+	let mut samples = vec![0.0; (length * SAMPLE_RATE as f32) as usize];
+	// Noise that is the same every time, any would do.
+	let mut noise_state: u32 = 0x9e3779b9;
+	for note in notes {
+		let first = (note.start * SAMPLE_RATE as f32) as usize;
+		let count = (note.duration * SAMPLE_RATE as f32) as usize;
+		let mut phase = 0.0f32;
+		for i in 0..count {
+			let Some(sample) = samples.get_mut(first + i) else {
+				break;
+			};
+			let progression = i as f32 / count as f32;
+			let attack = (i as f32 / (0.005 * SAMPLE_RATE as f32)).min(1.0);
+			let envelope = attack * (1.0 - progression) * (1.0 - progression);
+			phase = (phase + (note.from + (note.to - note.from) * progression) / SAMPLE_RATE as f32)
+				.fract();
+			let value = match note.wave {
+				Wave::Sine => (phase * TAU).sin(),
+				Wave::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+				Wave::Noise => {
+					noise_state ^= noise_state << 13;
+					noise_state ^= noise_state >> 17;
+					noise_state ^= noise_state << 5;
+					noise_state as f32 / u32::MAX as f32 * 2.0 - 1.0
+				},
+			};
+			*sample += value * envelope * note.volume;
+		}
+	}
+	samples
+}
+
+/// Encodes samples (from -1 to 1) as a mono 16-bit WAV file.
+fn wav(samples: &[f32]) -> Vec<u8> {
+	let data_size = samples.len() as u32 * 2;
+	let mut bytes = Vec::with_capacity(44 + data_size as usize);
+	bytes.extend_from_slice(b"RIFF");
+	bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+	bytes.extend_from_slice(b"WAVE");
+	bytes.extend_from_slice(b"fmt ");
+	bytes.extend_from_slice(&16u32.to_le_bytes());
+	// PCM, mono.
+	bytes.extend_from_slice(&1u16.to_le_bytes());
+	bytes.extend_from_slice(&1u16.to_le_bytes());
+	bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+	bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+	// Bytes per sample and bits per sample.
+	bytes.extend_from_slice(&2u16.to_le_bytes());
+	bytes.extend_from_slice(&16u16.to_le_bytes());
+	bytes.extend_from_slice(b"data");
+	bytes.extend_from_slice(&data_size.to_le_bytes());
+	for sample in samples {
+		let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+		bytes.extend_from_slice(&sample.to_le_bytes());
+	}
+	bytes
+}
+
+/// A WAV file in memory, that can be decoded any number of times.
+#[cfg(feature = "audio")]
+type SoundData = Arc<[u8]>;
+
+#[cfg(feature = "audio")]
+struct MusicTrack {
+	music: Music,
+	data: SoundData,
+	/// Playing the music in a loop, while it is not silent.
+	sink: Option<Sink>,
+	/// From 0 (silent, and stopped) to 1 (at the volume of the settings).
+	fade: f32,
+}
+
+enum Backend {
+	/// Plays nothing.
+	Null,
+	#[cfg(feature = "audio")]
+	Rodio {
+		/// The sounds play as long as the stream is kept.
+		_stream: OutputStream,
+		stream_handle: OutputStreamHandle,
+		effects: Vec<(SoundEffect, SoundData)>,
+		tracks: Vec<MusicTrack>,
+	},
+}
+
+#[cfg(feature = "audio")]
+fn audio_error(error: impl std::fmt::Display) -> ggez::GameError {
+	ggez::GameError::AudioError(error.to_string())
+}
+
+struct AudioContent {
+	backend: Backend,
+	/// The effects to play at the next update (that has the context needed to play them).
+	queued_effects: Vec<SoundEffect>,
+	music: Option<Music>,
+}
+
+/// The sound effects and the music. Scenes only queue effects, the scene stack picks
+/// the music of the top scene that has one and plays it all at its update.
+#[derive(Clone)]
+pub struct Audio {
+	content: Rc<RefCell<AudioContent>>,
+}
+
+impl Audio {
+	const CROSSFADE_DURATION: Duration = Duration::from_millis(1500);
+
+	fn with_backend(backend: Backend) -> Audio {
+		let content = AudioContent { backend, queued_effects: vec![], music: None };
+		Audio { content: Rc::new(RefCell::new(content)) }
+	}
+
+	/// Opens the audio device and synthesizes the sounds. It fails if there is no audio
+	/// device, the null backend can then be used instead.
+	#[cfg(feature = "audio")]
+	pub fn new() -> GameResult<Audio> {
+		let (stream, stream_handle) = OutputStream::try_default().map_err(audio_error)?;
+		let effects = SoundEffect::ALL
+			.into_iter()
+			.map(|effect| {
+				let notes = effect.notes();
+				let length = notes.iter().map(|note| note.start + note.duration).fold(0.0, f32::max);
+				let samples = render(length, &notes);
+				(effect, SoundData::from(wav(&samples)))
+			})
+			.collect();
+		let tracks = Music::ALL
+			.into_iter()
+			.map(|music| {
+				let data = SoundData::from(wav(&music.samples()));
+				MusicTrack { music, data, sink: None, fade: 0.0 }
+			})
+			.collect();
+		Ok(Audio::with_backend(Backend::Rodio { _stream: stream, stream_handle, effects, tracks }))
+	}
+
+	/// Without the `audio` feature, there is only the null backend.
+	#[cfg(not(feature = "audio"))]
+	pub fn new() -> GameResult<Audio> {
+		Ok(Audio::null())
+	}
+
+	/// Audio that plays nothing, for when there is no audio device (or no need for sound).
+	pub fn null() -> Audio {
+		Audio::with_backend(Backend::Null)
+	}
+
+	/// The effect is played at the next update.
+	pub fn play(&self, effect: SoundEffect) {
+		self.content.borrow_mut().queued_effects.push(effect);
+	}
+
+	/// The music that should be playing, it crossfades from the previous one.
+	pub fn set_music(&self, music: Option<Music>) {
+		self.content.borrow_mut().music = music;
+	}
+
+	/// Plays the queued effects and goes on with the crossfade, with the volumes
+	/// of the settings. Fades are timed with the wall clock, like the music itself.
+	#[cfg_attr(not(feature = "audio"), allow(unused_variables))]
+	pub fn update(&self, wall_delta: Duration, settings: &Settings) -> GameResult {
+		let mut content = self.content.borrow_mut();
+		let queued_effects = std::mem::take(&mut content.queued_effects);
+		let music = content.music;
+		match &mut content.backend {
+			Backend::Null => {},
+			#[cfg(feature = "audio")]
+			Backend::Rodio { stream_handle, effects, tracks, .. } => {
+				let master_volume = settings.master_volume as f32 / 100.0;
+				let sfx_volume = master_volume * settings.sfx_volume as f32 / 100.0;
+				let music_volume = master_volume * settings.music_volume as f32 / 100.0;
+				for effect in queued_effects {
+					let Some((_effect, data)) = effects.iter().find(|(other, _data)| *other == effect)
+					else {
+						continue;
+					};
+					let sink = Sink::try_new(stream_handle).map_err(audio_error)?;
+					sink.set_volume(sfx_volume);
+					sink.append(Decoder::new(Cursor::new(data.clone())).map_err(audio_error)?);
+					sink.detach();
+				}
+				let fade_step = wall_delta.as_secs_f32() / Audio::CROSSFADE_DURATION.as_secs_f32();
+				for track in tracks.iter_mut() {
+					let was_silent = track.fade == 0.0;
+					track.fade = if Some(track.music) == music {
+						(track.fade + fade_step).min(1.0)
+					} else {
+						(track.fade - fade_step).max(0.0)
+					};
+					// Music that fades back in starts over.
+					if was_silent && track.fade > 0.0 {
+						let sink = Sink::try_new(stream_handle).map_err(audio_error)?;
+						let data = Cursor::new(track.data.clone());
+						sink.append(Decoder::new_looped(data).map_err(audio_error)?);
+						track.sink = Some(sink);
+					} else if !was_silent && track.fade == 0.0 {
+						track.sink = None;
+					}
+					if let Some(sink) = &track.sink {
+						sink.set_volume(track.fade * music_volume);
+					}
+				}
+			},
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn null_audio_takes_everything() {
+		let audio = Audio::null();
+		audio.clone().play(SoundEffect::Hit);
+		audio.set_music(Some(Music::Battle));
+		let content = audio.content.borrow();
+		assert!(matches!(content.backend, Backend::Null));
+		assert_eq!(content.queued_effects.len(), 1);
+		assert!(content.music == Some(Music::Battle));
+	}
+
+	#[test]
+	fn wav_has_a_header_and_16_bit_samples() {
+		let bytes = wav(&[0.0, 1.0, -1.0, 2.0]);
+		assert_eq!(bytes.len(), 44 + 4 * 2);
+		assert_eq!(&bytes[0..4], b"RIFF");
+		assert_eq!(&bytes[8..12], b"WAVE");
+		assert_eq!(&bytes[36..40], b"data");
+		assert_eq!(&bytes[40..44], &8u32.to_le_bytes());
+		// Samples past 1 are clamped.
+		assert_eq!(&bytes[46..48], &i16::MAX.to_le_bytes());
+		assert_eq!(&bytes[50..52], &i16::MAX.to_le_bytes());
+	}
+
+	#[test]
+	fn sounds_do_not_clip() {
+		let sounds = SoundEffect::ALL
+			.into_iter()
+			.map(|effect| render(1.0, &effect.notes()))
+			.chain(Music::ALL.into_iter().map(Music::samples));
+		for samples in sounds {
+			assert!(samples.iter().all(|sample| sample.abs() <= 1.0));
+		}
+	}
+
+	#[test]
+	fn music_loops_without_a_click() {
+		for music in Music::ALL {
+			let samples = music.samples();
+			assert!(samples.last().unwrap().abs() < 0.01);
+		}
+	}
+
+	#[cfg(feature = "audio")]
+	#[test]
+	fn sounds_can_be_decoded() {
+		use rodio::Source;
+		let sounds = SoundEffect::ALL
+			.into_iter()
+			.map(|effect| render(1.0, &effect.notes()))
+			.chain(Music::ALL.into_iter().map(Music::samples));
+		for samples in sounds {
+			let decoder = Decoder::new(Cursor::new(SoundData::from(wav(&samples)))).unwrap();
+			assert_eq!(decoder.sample_rate(), SAMPLE_RATE);
+		}
+	}
+}
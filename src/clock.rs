@@ -0,0 +1,153 @@
+//! The game clock, that animations are timed with (instead of the wall clock) so that
+//! they can be paused, sped up or slowed down, and advanced by exact amounts.
+//! It also keeps the wall time, for the feedback to the player (like error messages)
+//! that must stay long enough to be read, whatever the speed of the animations.
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// How fast the game clock goes compared to the wall clock.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ClockSpeed {
+	Half,
+	Normal,
+	Double,
+	/// Animations are over as soon as they start.
+	Instant,
+}
+
+impl ClockSpeed {
+	pub const ALL: [ClockSpeed; 4] =
+		[ClockSpeed::Half, ClockSpeed::Normal, ClockSpeed::Double, ClockSpeed::Instant];
+
+	/// The name used in the settings menu and file.
+	pub fn name(self) -> &'static str {
+		match self {
+			ClockSpeed::Half => "0.5x",
+			ClockSpeed::Normal => "1x",
+			ClockSpeed::Double => "2x",
+			ClockSpeed::Instant => "instant",
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<ClockSpeed> {
+		ClockSpeed::ALL.into_iter().find(|speed| speed.name() == name)
+	}
+
+	/// The speed that comes after this one in the settings menu.
+	pub fn next(self) -> ClockSpeed {
+		let i = ClockSpeed::ALL.iter().position(|speed| *speed == self).unwrap();
+		ClockSpeed::ALL[(i + 1) % ClockSpeed::ALL.len()]
+	}
+}
+
+/// The time animations and the battle follow. Only the scene stack moves it forward,
+/// once per update (scaled by the animation speed setting), every scene just reads it.
+#[derive(Clone)]
+pub struct GameClock {
+	now: Rc<Cell<Duration>>,
+	wall_now: Rc<Cell<Duration>>,
+	paused: Rc<Cell<bool>>,
+}
+
+impl GameClock {
+	/// With `ClockSpeed::Instant`, the clock jumps that far ahead at every update,
+	/// which is more than any animation lasts.
+	const INSTANT_STEP: Duration = Duration::from_secs(3600);
+
+	pub fn new() -> GameClock {
+		GameClock {
+			now: Rc::new(Cell::new(Duration::ZERO)),
+			wall_now: Rc::new(Cell::new(Duration::ZERO)),
+			paused: Rc::new(Cell::new(false)),
+		}
+	}
+
+	/// Game time elapsed since the clock was created.
+	pub fn now(&self) -> Duration {
+		self.now.get()
+	}
+
+	/// Wall clock time elapsed since the clock was created, that goes on at the same speed
+	/// whatever the clock speed, and even when the clock is paused.
+	pub fn wall_now(&self) -> Duration {
+		self.wall_now.get()
+	}
+
+	/// Advances the clock by exactly that much game time (even if it is paused).
+	pub fn advance(&self, delta: Duration) {
+		self.now.set(self.now.get() + delta);
+	}
+
+	/// Advances the clock by wall clock time (usually the time since the last update),
+	/// at the given speed, unless it is paused.
+	pub fn tick(&self, wall_delta: Duration, speed: ClockSpeed) {
+		self.wall_now.set(self.wall_now.get() + wall_delta);
+		if self.paused.get() {
+			return;
+		}
+		self.advance(match speed {
+			ClockSpeed::Half => wall_delta / 2,
+			ClockSpeed::Normal => wall_delta,
+			ClockSpeed::Double => wall_delta * 2,
+			ClockSpeed::Instant => GameClock::INSTANT_STEP,
+		});
+	}
+
+	pub fn set_paused(&self, paused: bool) {
+		self.paused.set(paused);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn tick_goes_at_the_clock_speed() {
+		let second = Duration::from_secs(1);
+		for (speed, expected) in [
+			(ClockSpeed::Half, Duration::from_millis(500)),
+			(ClockSpeed::Normal, second),
+			(ClockSpeed::Double, Duration::from_secs(2)),
+			(ClockSpeed::Instant, GameClock::INSTANT_STEP),
+		] {
+			let clock = GameClock::new();
+			clock.tick(second, speed);
+			assert_eq!(clock.now(), expected, "{}", speed.name());
+			assert_eq!(clock.wall_now(), second, "{}", speed.name());
+		}
+	}
+
+	#[test]
+	fn paused_clock_only_keeps_wall_time() {
+		let clock = GameClock::new();
+		clock.set_paused(true);
+		clock.tick(Duration::from_secs(1), ClockSpeed::Normal);
+		assert_eq!(clock.now(), Duration::ZERO);
+		assert_eq!(clock.wall_now(), Duration::from_secs(1));
+		clock.advance(Duration::from_millis(250));
+		assert_eq!(clock.now(), Duration::from_millis(250));
+		clock.set_paused(false);
+		clock.tick(Duration::from_secs(1), ClockSpeed::Normal);
+		assert_eq!(clock.now(), Duration::from_millis(1250));
+	}
+
+	#[test]
+	fn handles_share_the_same_clock() {
+		let clock = GameClock::new();
+		let other = clock.clone();
+		clock.advance(Duration::from_secs(3));
+		assert_eq!(other.now(), Duration::from_secs(3));
+	}
+
+	#[test]
+	fn speed_names_round_trip() {
+		for speed in ClockSpeed::ALL {
+			assert!(ClockSpeed::from_name(speed.name()) == Some(speed));
+		}
+		assert!(ClockSpeed::from_name("3x").is_none());
+		assert!(ClockSpeed::Instant.next() == ClockSpeed::Half);
+	}
+}
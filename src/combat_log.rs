@@ -0,0 +1,244 @@
+//! The combat log, that tells in plain language what happened during the battle
+//! (a lot can happen in a single turn, with all the reactions). It is displayed in a panel
+//! next to the battlefield, and it can be exported as a text file to be pasted in bug reports.
+
+use std::io::Write;
+
+use ggez::glam::*;
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, Rect, Text};
+use ggez::{Context, GameResult};
+
+use crate::clock::GameClock;
+use crate::rich_text::{self, RichText};
+use crate::spritesheet::Spritesheet;
+use crate::{CreatureId, TimeProgression};
+
+/// Where the log is exported, in the same directory as the settings.
+const EXPORT_FILE: &str = "/combat_log.txt";
+
+#[derive(Clone)]
+pub struct LogEntry {
+	/// In rich text markup.
+	pub text: String,
+	/// The creatures involved, they get highlighted when the entry is hovered.
+	pub creatures: Vec<CreatureId>,
+}
+
+#[derive(Clone, Default)]
+pub struct CombatLog {
+	entries: Vec<LogEntry>,
+}
+
+impl CombatLog {
+	pub fn push(&mut self, text: String, creatures: Vec<CreatureId>) {
+		self.entries.push(LogEntry { text, creatures });
+	}
+
+	/// Forgets the entries after the first `len` ones.
+	pub fn truncate(&mut self, len: usize) {
+		self.entries.truncate(len);
+	}
+
+	pub fn entries(&self) -> &[LogEntry] {
+		&self.entries
+	}
+
+	/// The whole log as plain text, one entry per line.
+	pub fn to_text(&self) -> String {
+		self.entries.iter().map(|entry| format!("{}\n", rich_text::plain(&entry.text))).collect()
+	}
+
+	pub fn export(&self, ctx: &Context) -> GameResult {
+		let mut file = ctx.fs.create(EXPORT_FILE)?;
+		file.write_all(self.to_text().as_bytes())?;
+		Ok(())
+	}
+}
+
+/// The panel that displays the log, with the most recent entries at the bottom.
+pub struct LogPanel {
+	/// For the icons in the entries.
+	spritesheet: Spritesheet,
+	/// How far up the log is scrolled. At 0 the most recent entries are shown,
+	/// and new entries are followed as they come.
+	scroll: f32,
+	/// Each entry laid out (wrapped to the width of the panel) once, with its height.
+	entry_texts: Vec<RichText>,
+	entry_heights: Vec<f32>,
+	/// The width the entries were wrapped to, they are laid out again if it changes.
+	measured_width: f32,
+	/// Where the entries are displayed, and where each visible entry was last drawn
+	/// (to know which one is hovered).
+	entries_area: Rect,
+	entry_rects: Vec<(usize, Rect)>,
+	/// Shown for a moment in the header after exporting the log.
+	export_message: Option<(TimeProgression, &'static str)>,
+}
+
+impl LogPanel {
+	const HEADER_HEIGHT: f32 = 34.0;
+	const PADDING: f32 = 8.0;
+	const TEXT_SIZE: f32 = 16.0;
+	const WHEEL_SCROLL_SPEED: f32 = 40.0;
+
+	pub fn new(spritesheet: &Spritesheet) -> LogPanel {
+		LogPanel {
+			spritesheet: spritesheet.clone(),
+			scroll: 0.0,
+			entry_texts: vec![],
+			entry_heights: vec![],
+			measured_width: 0.0,
+			entries_area: Rect::default(),
+			entry_rects: vec![],
+			export_message: None,
+		}
+	}
+
+	fn export_button_rect(rect: Rect, scale: f32) -> Rect {
+		let (w, h) = (80.0 * scale, 24.0 * scale);
+		Rect::new(rect.right() - w - LogPanel::PADDING * scale, rect.top() + 5.0 * scale, w, h)
+	}
+
+	fn max_scroll(&self) -> f32 {
+		let content_height: f32 = self.entry_heights.iter().sum();
+		(content_height - self.entries_area.h).max(0.0)
+	}
+
+	/// Forgets the laid out entries, for when the log is replaced by another one
+	/// (like an older version of itself after an undo) instead of just growing.
+	pub fn reset(&mut self) {
+		self.entry_texts.clear();
+		self.entry_heights.clear();
+		self.entry_rects.clear();
+	}
+
+	/// Scrolls the log when the wheel is used over the panel.
+	pub fn mouse_wheel(&mut self, y: f32) {
+		self.scroll = (self.scroll + y * LogPanel::WHEEL_SCROLL_SPEED).clamp(0.0, self.max_scroll());
+	}
+
+	/// The entry under the given point, if any.
+	pub fn hovered_entry(&self, pos: Vec2) -> Option<usize> {
+		if !self.entries_area.contains(pos) {
+			return None;
+		}
+		self.entry_rects.iter().find(|(_i, rect)| rect.contains(pos)).map(|(i, _rect)| *i)
+	}
+
+	/// Handles a click on the panel, returns `true` if the click was for the panel.
+	pub fn click(
+		&mut self,
+		ctx: &Context,
+		clock: &GameClock,
+		log: &CombatLog,
+		rect: Rect,
+		scale: f32,
+		pos: Vec2,
+	) -> bool {
+		if LogPanel::export_button_rect(rect, scale).contains(pos) {
+			let message = match log.export(ctx) {
+				Ok(()) => "saved to combat_log.txt",
+				Err(_) => "could not save the log",
+			};
+			let duration = std::time::Duration::from_secs_f32(3.0);
+			let tp = TimeProgression::with_wall_duration(clock, duration);
+			self.export_message = Some((tp, message));
+		}
+		rect.contains(pos)
+	}
+
+	pub fn draw(
+		&mut self,
+		ctx: &mut Context,
+		canvas: &mut Canvas,
+		log: &CombatLog,
+		rect: Rect,
+		scale: f32,
+		cursor_pos: Option<Vec2>,
+	) -> GameResult {
+		let background =
+			Mesh::new_rectangle(ctx, DrawMode::fill(), rect, Color::from([0.0, 0.0, 0.0, 0.4]))?;
+		canvas.draw(&background, Vec2::new(0.0, 0.0));
+		let padding = LogPanel::PADDING * scale;
+
+		// The header, with the export button (or what happened when it was last clicked).
+		canvas.draw(
+			Text::new("log").set_scale(22.0 * scale),
+			DrawParam::from(Vec2::new(rect.left() + padding, rect.top() + 5.0 * scale))
+				.color(Color::WHITE),
+		);
+		if self.export_message.as_ref().is_some_and(|(tp, _message)| tp.progression() >= 1.0) {
+			self.export_message = None;
+		}
+		let button_rect = LogPanel::export_button_rect(rect, scale);
+		if let Some((_tp, message)) = &self.export_message {
+			let mut text = Text::new(*message);
+			text.set_scale(LogPanel::TEXT_SIZE * scale);
+			let text_width = Vec2::from(text.measure(ctx)?).x;
+			let pos = Vec2::new(button_rect.right() - text_width, button_rect.top() + 4.0 * scale);
+			canvas.draw(&text, DrawParam::from(pos).color(Color::from_rgb(180, 255, 180)));
+		} else {
+			let hovered = cursor_pos.is_some_and(|pos| button_rect.contains(pos));
+			let color = if hovered { Color::from_rgb(255, 220, 120) } else { Color::WHITE };
+			let outline = Mesh::new_rectangle(ctx, DrawMode::stroke(2.0), button_rect, color)?;
+			canvas.draw(&outline, Vec2::new(0.0, 0.0));
+			let pos = Vec2::new(button_rect.left() + 10.0 * scale, button_rect.top() + 4.0 * scale);
+			canvas.draw(
+				Text::new("export").set_scale(LogPanel::TEXT_SIZE * scale),
+				DrawParam::from(pos).color(color),
+			);
+		}
+
+		// The entries, from the most recent one at the bottom, upwards.
+		let header_height = LogPanel::HEADER_HEIGHT * scale;
+		self.entries_area = Rect::new(
+			rect.left() + padding,
+			rect.top() + header_height,
+			rect.w - padding * 2.0,
+			rect.h - header_height - padding,
+		);
+		let area = self.entries_area;
+		if area.w < 1.0 || area.h < 1.0 {
+			return Ok(());
+		}
+		if self.measured_width != area.w {
+			self.measured_width = area.w;
+			self.entry_texts.clear();
+			self.entry_heights.clear();
+		}
+		for entry in log.entries()[self.entry_texts.len()..].iter() {
+			let text = RichText::new(ctx, &entry.text, LogPanel::TEXT_SIZE * scale, area.w)?;
+			self.entry_heights.push(text.dims().y + 4.0 * scale);
+			self.entry_texts.push(text);
+		}
+		self.scroll = self.scroll.min(self.max_scroll());
+
+		canvas.set_scissor_rect(area)?;
+		self.entry_rects.clear();
+		let mut y = area.bottom() + self.scroll;
+		for i in (0..log.entries().len()).rev() {
+			y -= self.entry_heights[i];
+			if y > area.bottom() {
+				continue;
+			}
+			if y + self.entry_heights[i] < area.top() {
+				break;
+			}
+			let entry_rect = Rect::new(area.left(), y, area.w, self.entry_heights[i]);
+			if cursor_pos.is_some_and(|pos| area.contains(pos) && entry_rect.contains(pos)) {
+				let highlight = Mesh::new_rectangle(
+					ctx,
+					DrawMode::fill(),
+					entry_rect,
+					Color::from([1.0, 1.0, 1.0, 0.15]),
+				)?;
+				canvas.draw(&highlight, Vec2::new(0.0, 0.0));
+			}
+			let pos = Vec2::from(entry_rect.point());
+			self.entry_texts[i].draw(ctx, canvas, &self.spritesheet, pos, Color::WHITE)?;
+			self.entry_rects.push((i, entry_rect));
+		}
+		canvas.set_default_scissor_rect();
+		Ok(())
+	}
+}
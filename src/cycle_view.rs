@@ -0,0 +1,235 @@
+//! How the action cycles of the creatures are displayed. Changes are animated so that
+//! players can follow the cycles turning (the main idea of the game): when a creature acts,
+//! its top action slides to the bottom of the stack and the others move up, and actions
+//! that are added or removed fade in or out.
+
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use crate::clock::GameClock;
+use crate::tween::{Easing, Tween};
+use crate::{Action, Creature, CreatureId, TimeProgression};
+
+#[derive(Clone, Copy)]
+enum Fade {
+	None,
+	In,
+	Out,
+}
+
+/// An action box, moving from a slot of the stack to another (0 being the top).
+struct ActionBox {
+	action: Action,
+	from_slot: f32,
+	to_slot: f32,
+	fade: Fade,
+}
+
+/// Where to draw an action box, at some point of the animation.
+pub struct BoxPlacement {
+	/// The slot of the stack the box is at, not necessarily a whole number.
+	pub slot: f32,
+	/// How far to the side the box is, from 0 to 1. Boxes that go around the cycle
+	/// get out of the way of the others.
+	pub sideways: f32,
+	pub alpha: f32,
+}
+
+struct CycleView {
+	creature: CreatureId,
+	/// The cycle as it was last seen.
+	actions: Vec<Action>,
+	rotations: u32,
+	boxes: Vec<ActionBox>,
+	tp: TimeProgression,
+}
+
+impl CycleView {
+	/// A cycle displayed as it is, with nothing to animate.
+	fn still(
+		creature: CreatureId,
+		actions: &[Action],
+		rotations: u32,
+		clock: &GameClock,
+	) -> CycleView {
+		let boxes = actions
+			.iter()
+			.enumerate()
+			.map(|(slot, action)| ActionBox {
+				action: action.clone(),
+				from_slot: slot as f32,
+				to_slot: slot as f32,
+				fade: Fade::None,
+			})
+			.collect();
+		CycleView {
+			creature,
+			actions: actions.to_vec(),
+			rotations,
+			boxes,
+			tp: TimeProgression::with_duration(clock, Duration::ZERO),
+		}
+	}
+
+	/// Animates the boxes from the cycle as it was last seen to the given one.
+	fn animate_to(&mut self, actions: &[Action], rotations: u32, clock: &GameClock) {
+		// The cycle may also have turned backwards, when a card play is undone.
+		let turns = if self.actions.is_empty() {
+			0
+		} else {
+			(rotations as i64 - self.rotations as i64).rem_euclid(self.actions.len() as i64)
+				as usize
+		};
+		let mut old_slots: Vec<usize> = (0..self.actions.len()).collect();
+		old_slots.rotate_left(turns);
+		let old_actions: Vec<&Action> = old_slots.iter().map(|&slot| &self.actions[slot]).collect();
+		let new_actions: Vec<&Action> = actions.iter().collect();
+		let kept = common_subsequence(&old_actions, &new_actions);
+
+		let mut boxes = vec![];
+		for (old_index, &slot) in old_slots.iter().enumerate() {
+			let new_index = kept.iter().find(|(kept_old, _new)| *kept_old == old_index);
+			let (to_slot, fade) = match new_index {
+				Some((_old, new_index)) => (*new_index as f32, Fade::None),
+				None => (slot as f32, Fade::Out),
+			};
+			let action = self.actions[slot].clone();
+			boxes.push(ActionBox { action, from_slot: slot as f32, to_slot, fade });
+		}
+		for (new_index, action) in actions.iter().enumerate() {
+			if !kept.iter().any(|(_old, kept_new)| *kept_new == new_index) {
+				let slot = new_index as f32;
+				let action = action.clone();
+				boxes.push(ActionBox { action, from_slot: slot, to_slot: slot, fade: Fade::In });
+			}
+		}
+
+		self.actions = actions.to_vec();
+		self.rotations = rotations;
+		self.boxes = boxes;
+		self.tp = TimeProgression::with_duration(clock, CycleViews::ANIMATION_DURATION);
+	}
+}
+
+/// The longest sequence of actions found in the same order in both lists,
+/// as pairs of indices (in `old`, in `new`).
+fn common_subsequence(old: &[&Action], new: &[&Action]) -> Vec<(usize, usize)> {
+	// lengths[i][j] is the length of the longest common subsequence of old[i..] and new[j..].
+	let mut lengths = vec![vec![0; new.len() + 1]; old.len() + 1];
+	for i in (0..old.len()).rev() {
+		for j in (0..new.len()).rev() {
+			lengths[i][j] = if old[i] == new[j] {
+				lengths[i + 1][j + 1] + 1
+			} else {
+				lengths[i + 1][j].max(lengths[i][j + 1])
+			};
+		}
+	}
+	let mut pairs = vec![];
+	let (mut i, mut j) = (0, 0);
+	while i < old.len() && j < new.len() {
+		if old[i] == new[j] {
+			pairs.push((i, j));
+			i += 1;
+			j += 1;
+		} else if lengths[i + 1][j] >= lengths[i][j + 1] {
+			i += 1;
+		} else {
+			j += 1;
+		}
+	}
+	pairs
+}
+
+/// The displayed cycles of all the creatures on the battlefield.
+pub struct CycleViews {
+	clock: GameClock,
+	views: Vec<CycleView>,
+}
+
+impl CycleViews {
+	const ANIMATION_DURATION: Duration = Duration::from_millis(400);
+
+	pub fn new(clock: &GameClock) -> CycleViews {
+		CycleViews { clock: clock.clone(), views: vec![] }
+	}
+
+	/// Notices the changes in the cycles of the given creatures (the ones on the battlefield)
+	/// and starts animating them, except for the `waiting` ones that keep their cycle
+	/// as it was last seen for now.
+	pub fn sync<'a>(
+		&mut self,
+		creatures: impl Iterator<Item = &'a Creature>,
+		waiting: &[CreatureId],
+	) {
+		let mut views = vec![];
+		for creature in creatures {
+			let (actions, rotations) = (&creature.cycle.actions, creature.cycle.rotations);
+			match self.views.iter().position(|view| view.creature == creature.id) {
+				Some(i) => {
+					let mut view = self.views.swap_remove(i);
+					let changed = view.rotations != rotations || view.actions != *actions;
+					if changed && !waiting.contains(&creature.id) {
+						view.animate_to(actions, rotations, &self.clock);
+					}
+					views.push(view);
+				},
+				// New creatures just appear with their cycle, there is nothing to animate.
+				None => views.push(CycleView::still(creature.id, actions, rotations, &self.clock)),
+			}
+		}
+		self.views = views;
+	}
+
+	pub fn is_animating(&self) -> bool {
+		self.views.iter().any(|view| view.tp.progression() < 1.0)
+	}
+
+	/// The action boxes of the given creature, with where to draw them.
+	pub fn boxes(&self, creature: CreatureId) -> Vec<(&Action, BoxPlacement)> {
+		let Some(view) = self.views.iter().find(|view| view.creature == creature) else {
+			return vec![];
+		};
+		let progression = view.tp.progression();
+		let eased = Easing::EaseOut.apply(progression);
+		view.boxes
+			.iter()
+			.filter(|action_box| !(progression >= 1.0 && matches!(action_box.fade, Fade::Out)))
+			.map(|action_box| {
+				let slot = Tween::new(action_box.from_slot, action_box.to_slot, Easing::EaseOut)
+					.at(progression);
+				let goes_around = (action_box.to_slot - action_box.from_slot).abs() > 1.5;
+				let sideways = if goes_around { (eased * PI).sin() } else { 0.0 };
+				let alpha = match action_box.fade {
+					Fade::None => 1.0,
+					Fade::In => progression,
+					Fade::Out => 1.0 - progression,
+				};
+				(&action_box.action, BoxPlacement { slot, sideways, alpha })
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn common_subsequence_keeps_the_order() {
+		let (attack, defend, heal) = (Action::Attack(1), Action::Defend(1), Action::Heal(1));
+		let old = [&attack, &defend, &heal];
+		let new = [&defend, &heal, &attack];
+		assert_eq!(common_subsequence(&old, &new), vec![(1, 0), (2, 1)]);
+	}
+
+	#[test]
+	fn common_subsequence_of_same_and_disjoint_lists() {
+		let (attack, defend) = (Action::Attack(1), Action::Defend(2));
+		let stronger_attack = Action::Attack(2);
+		let actions = [&attack, &defend];
+		assert_eq!(common_subsequence(&actions, &actions), vec![(0, 0), (1, 1)]);
+		assert!(common_subsequence(&[&attack], &[&stronger_attack]).is_empty());
+		assert!(common_subsequence(&[], &actions).is_empty());
+	}
+}
@@ -0,0 +1,228 @@
+//! The values of the cards and the encounters, from `assets/cards.txt`, so that they can be
+//! tweaked without touching the code (and even while the game is running, see `hot_reload`).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use ggez::{GameError, GameResult};
+
+use crate::procedural_art::ProceduralArt;
+use crate::CardSpec;
+
+/// The stats of a creature when it is summoned or played.
+#[derive(Clone, Copy)]
+pub struct CreatureStats {
+	pub hp: i32,
+	pub anger: u32,
+	pub scary: u32,
+}
+
+struct GameDataContent {
+	creature_stats: HashMap<String, CreatureStats>,
+	/// The cards that are drawn procedurally instead of with a sprite.
+	art: HashMap<String, ProceduralArt>,
+	deck: Vec<CardSpec>,
+	/// The foes of each battle, the first one being the first battle.
+	encounters: Vec<Vec<CardSpec>>,
+}
+
+/// Parses the data, with one `name = value` per line (see the comments in the file itself).
+fn parse_game_data(text: &str) -> Result<GameDataContent, String> {
+	let mut values: HashMap<String, u32> = HashMap::new();
+	let mut art = HashMap::new();
+	let mut deck = None;
+	let mut encounters: Vec<(u32, Vec<CardSpec>)> = vec![];
+	for (line_index, line) in text.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		let error = |what: &str| format!("line {}: {what}", line_index + 1);
+		let Some((name, value)) = line.split_once('=') else {
+			return Err(error("expected \"name = value\""));
+		};
+		let (name, value) = (name.trim(), value.trim());
+		let cards = |value: &str| -> Result<Vec<CardSpec>, String> {
+			value
+				.split_whitespace()
+				.map(|id| CardSpec::from_id(id).ok_or_else(|| error(&format!("no card \"{id}\""))))
+				.collect()
+		};
+		if name == "deck" {
+			deck = Some(cards(value)?);
+		} else if let Some(number) = name.strip_prefix("encounter.") {
+			let number = number.parse().map_err(|_| error("expected \"encounter.N\""))?;
+			encounters.push((number, cards(value)?));
+		} else if let Some(id) = name.strip_suffix(".art") {
+			if CardSpec::from_id(id).is_none() {
+				return Err(error(&format!("no card \"{id}\"")));
+			}
+			art.insert(id.to_string(), ProceduralArt::parse(value, id).map_err(|what| error(&what))?);
+		} else {
+			let value = value.parse().map_err(|_| error(&format!("\"{value}\" is not a number")))?;
+			values.insert(name.to_string(), value);
+		}
+	}
+
+	let mut creature_stats = HashMap::new();
+	for card_spec in CardSpec::ALL.iter().filter(|card_spec| card_spec.is_creature()) {
+		let id = card_spec.id();
+		let mut value = |stat: &str| values.remove(&format!("{id}.{stat}"));
+		let hp = value("hp").ok_or_else(|| format!("{id}.hp is missing"))?;
+		let stats = CreatureStats {
+			hp: hp as i32,
+			anger: value("anger").unwrap_or(0),
+			scary: value("scary").unwrap_or(0),
+		};
+		creature_stats.insert(id.to_string(), stats);
+	}
+	if let Some(name) = values.keys().next() {
+		return Err(format!("unknown value \"{name}\""));
+	}
+	let deck = deck.ok_or("deck is missing")?;
+	// Once sorted, the encounters must be numbered 1, 2, 3... and none can be empty.
+	encounters.sort_by_key(|(number, _foes)| *number);
+	for (i, (number, foes)) in encounters.iter().enumerate() {
+		let expected = i as u32 + 1;
+		if *number < expected {
+			return Err(format!("encounter.{number} is defined twice"));
+		}
+		if *number > expected {
+			return Err(format!("encounter.{expected} is missing"));
+		}
+		if foes.is_empty() {
+			return Err(format!("encounter.{number} has no foes"));
+		}
+	}
+	if encounters.is_empty() {
+		return Err("encounter.1 is missing".to_string());
+	}
+	let encounters: Vec<Vec<CardSpec>> =
+		encounters.into_iter().map(|(_number, foes)| foes).collect();
+	Ok(GameDataContent { creature_stats, art, deck, encounters })
+}
+
+/// The creature stats, the starting deck and the encounters. Hot reloading replaces them
+/// in place, creatures already on the battlefield keep the stats they were made with.
+#[derive(Clone)]
+pub struct GameData {
+	content: Rc<RefCell<GameDataContent>>,
+}
+
+impl GameData {
+	/// The data that comes with the game.
+	pub fn load() -> GameResult<GameData> {
+		let content = parse_game_data(include_str!("../assets/cards.txt"))
+			.map_err(|error| GameError::ResourceLoadError(format!("cards.txt, {error}")))?;
+		Ok(GameData { content: Rc::new(RefCell::new(content)) })
+	}
+
+	/// Replaces the data by the given one, unless it does not parse.
+	pub fn reload(&self, text: &str) -> Result<(), String> {
+		*self.content.borrow_mut() = parse_game_data(text)?;
+		Ok(())
+	}
+
+	/// The stats of a creature card (`None` for the other cards).
+	pub fn creature_stats(&self, card_spec: &CardSpec) -> Option<CreatureStats> {
+		self.content.borrow().creature_stats.get(card_spec.id()).copied()
+	}
+
+	/// The procedural art of a card, if it has no sprite.
+	pub fn art(&self, card_spec: &CardSpec) -> Option<ProceduralArt> {
+		self.content.borrow().art.get(card_spec.id()).copied()
+	}
+
+	pub fn deck(&self) -> Vec<CardSpec> {
+		self.content.borrow().deck.clone()
+	}
+
+	/// The foes of the given battle of a run (starting at 1).
+	pub fn encounter(&self, battle_number: u32) -> Vec<CardSpec> {
+		let content = self.content.borrow();
+		let index = battle_number.saturating_sub(1) as usize;
+		match content.encounters.get(index) {
+			Some(foes) => foes.clone(),
+			None => {
+				let mut foes = content.encounters.last().unwrap().clone();
+				let extra_foes = index + 1 - content.encounters.len();
+				let last_foe = foes.last().unwrap().clone();
+				foes.extend(std::iter::repeat_n(last_foe, extra_foes));
+				foes
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const MINIMAL: &str = "fwog.hp = 4\ndragon_fly.hp = 4\ntadpole.hp = 1\n\
+		deck = fwog food\nencounter.1 = dragon_fly\n";
+
+	#[test]
+	fn the_embedded_data_parses() {
+		let data = GameData::load().unwrap();
+		assert!(!data.deck().is_empty());
+		assert!(data.creature_stats(&CardSpec::DragonFly).is_some());
+		assert!(data.creature_stats(&CardSpec::Food).is_none());
+	}
+
+	#[test]
+	fn missing_values_have_defaults() {
+		let content = parse_game_data(MINIMAL).unwrap();
+		let stats = content.creature_stats["fwog"];
+		assert_eq!((stats.hp, stats.anger, stats.scary), (4, 0, 0));
+		assert!(content.art.is_empty());
+	}
+
+	#[test]
+	fn encounters_past_the_last_one_get_more_foes() {
+		let data = GameData::load().unwrap();
+		data.reload(&format!("{MINIMAL}encounter.2 = tadpole dragon_fly\n")).unwrap();
+		assert!(data.encounter(1) == vec![CardSpec::DragonFly]);
+		assert!(data.encounter(2) == vec![CardSpec::Tadpole, CardSpec::DragonFly]);
+		let (tadpole, dragon_fly) = (CardSpec::Tadpole, CardSpec::DragonFly);
+		let fourth = [tadpole, dragon_fly.clone(), dragon_fly.clone(), dragon_fly];
+		assert!(data.encounter(4) == fourth);
+	}
+
+	#[test]
+	fn a_failed_reload_keeps_the_data() {
+		let data = GameData::load().unwrap();
+		let deck_size = data.deck().len();
+		assert!(data.reload("deck = fwog").is_err());
+		assert_eq!(data.deck().len(), deck_size);
+	}
+
+	#[test]
+	fn reports_errors() {
+		for (extra, error) in [
+			("nonsense", "line 6: expected \"name = value\""),
+			("fwog.anger = many", "line 6: \"many\" is not a number"),
+			("fwog.shiny = 1", "unknown value \"fwog.shiny\""),
+			("deck = fwog frog", "line 6: no card \"frog\""),
+			("frog.art = sides 3", "line 6: no card \"frog\""),
+			("fwog.art = sides three", "line 6: \"sides three\" is not made of numbers"),
+			("encounter.one = fwog", "line 6: expected \"encounter.N\""),
+			("encounter.3 = fwog", "encounter.2 is missing"),
+			("encounter.1 = fwog", "encounter.1 is defined twice"),
+			("encounter.2 =\nencounter.3 = fwog", "encounter.2 has no foes"),
+		] {
+			let error_found = parse_game_data(&format!("{MINIMAL}{extra}\n")).err();
+			assert_eq!(error_found.as_deref(), Some(error), "{extra}");
+		}
+		let without = |line: &str| MINIMAL.replace(line, "");
+		let missing_hp = parse_game_data(&without("tadpole.hp = 1\n")).err();
+		assert_eq!(missing_hp.as_deref(), Some("tadpole.hp is missing"));
+		let missing_deck = parse_game_data(&without("deck = fwog food\n")).err();
+		assert_eq!(missing_deck.as_deref(), Some("deck is missing"));
+		let no_foes = MINIMAL.replace("encounter.1 = dragon_fly", "encounter.1 =");
+		let no_foes = parse_game_data(&no_foes).err();
+		assert_eq!(no_foes.as_deref(), Some("encounter.1 has no foes"));
+		let no_encounters = parse_game_data(&without("encounter.1 = dragon_fly\n")).err();
+		assert_eq!(no_encounters.as_deref(), Some("encounter.1 is missing"));
+	}
+}
@@ -0,0 +1,121 @@
+//! Dev mode (when the game is started with `--dev`): the assets and the game data
+//! are loaded from the `assets` directory of the repository instead of the copies embedded
+//! in the executable, and they are reloaded whenever they change on disk, without
+//! restarting the game (or even the current battle). Files that fail to load are reported
+//! with an error toast, and the previous version is kept.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use ggez::Context;
+
+use crate::scene::Shared;
+use crate::toasts::ToastKind;
+
+#[derive(Clone, Copy)]
+enum Asset {
+	SpritesheetImage,
+	SpritesheetAtlas,
+	GameData,
+}
+
+impl Asset {
+	const ALL: [Asset; 3] = [Asset::SpritesheetImage, Asset::SpritesheetAtlas, Asset::GameData];
+
+	fn file_name(self) -> &'static str {
+		match self {
+			Asset::SpritesheetImage => "spritesheet.png",
+			Asset::SpritesheetAtlas => "spritesheet.atlas",
+			Asset::GameData => "cards.txt",
+		}
+	}
+
+	/// Loads the asset from its file into the shared state.
+	fn reload(self, ctx: &Context, shared: &Shared, path: &Path) -> Result<(), String> {
+		match self {
+			Asset::SpritesheetImage => {
+				let bytes = std::fs::read(path).map_err(|error| error.to_string())?;
+				shared.spritesheet.reload_image(ctx, &bytes).map_err(|error| error.to_string())
+			},
+			Asset::SpritesheetAtlas => {
+				let text = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+				shared.spritesheet.reload_atlas(&text)
+			},
+			Asset::GameData => {
+				let text = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+				shared.data.reload(&text)
+			},
+		}
+	}
+}
+
+struct WatchedFile {
+	asset: Asset,
+	path: PathBuf,
+	/// When the file was modified, as of the last time it was loaded.
+	modified: Option<SystemTime>,
+}
+
+pub struct HotReload {
+	files: Vec<WatchedFile>,
+	since_last_check: Duration,
+	is_first_check: bool,
+}
+
+impl HotReload {
+	/// The files are checked for changes that often (looking at the modification time
+	/// of a few files is cheap, but not something to do every frame).
+	const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+	/// Is the game started in dev mode?
+	pub fn is_dev_mode() -> bool {
+		std::env::args().any(|arg| arg == "--dev")
+	}
+
+	/// Nothing is loaded yet, so everything is loaded from disk at the first update.
+	pub fn new() -> HotReload {
+		let assets_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets");
+		let files = Asset::ALL
+			.into_iter()
+			.map(|asset| {
+				let path = assets_dir.join(asset.file_name());
+				WatchedFile { asset, path, modified: None }
+			})
+			.collect();
+		HotReload { files, since_last_check: HotReload::CHECK_INTERVAL, is_first_check: true }
+	}
+
+	/// Reloads the files that changed since the last check (if it is time to check).
+	pub fn update(&mut self, ctx: &Context, shared: &mut Shared, wall_delta: Duration) {
+		self.since_last_check += wall_delta;
+		if self.since_last_check < HotReload::CHECK_INTERVAL {
+			return;
+		}
+		self.since_last_check = Duration::ZERO;
+		let is_first_check = std::mem::replace(&mut self.is_first_check, false);
+		for file in self.files.iter_mut() {
+			let file_name = file.asset.file_name();
+			let modified = std::fs::metadata(&file.path).and_then(|metadata| metadata.modified());
+			let modified = match modified {
+				Ok(modified) => modified,
+				Err(error) => {
+					// Only reported once, not at every check.
+					if is_first_check {
+						shared.toasts.push(ToastKind::Error, format!("{file_name}: {error}"));
+					}
+					continue;
+				},
+			};
+			if file.modified == Some(modified) {
+				continue;
+			}
+			// Even if it fails, it is not tried again until the file changes again.
+			file.modified = Some(modified);
+			match file.asset.reload(ctx, shared, &file.path) {
+				Ok(()) if is_first_check => {},
+				Ok(()) => shared.toasts.push(ToastKind::Info, format!("reloaded {file_name}")),
+				Err(error) => shared.toasts.push(ToastKind::Error, format!("{file_name}: {error}")),
+			}
+		}
+	}
+}
@@ -0,0 +1,254 @@
+//! Keyboard and gamepad inputs, translated into commands so that the game can be played
+//! without a mouse. Keys can be rebound in the settings, gamepad buttons cannot (yet).
+
+use ggez::event::Button;
+use ggez::input::keyboard::KeyCode;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+	/// Moves the focus to the previous hand card (or target, or menu button).
+	Previous,
+	Next,
+	/// Selects the focused hand card, or plays the selected card on the focused target,
+	/// or clicks the focused button.
+	Confirm,
+	/// Deselects the selected hand card.
+	Cancel,
+	EndTurn,
+	/// Takes back the last card played during the turn.
+	Undo,
+	Pause,
+}
+
+impl Command {
+	pub const ALL: [Command; 7] = [
+		Command::Previous,
+		Command::Next,
+		Command::Confirm,
+		Command::Cancel,
+		Command::EndTurn,
+		Command::Undo,
+		Command::Pause,
+	];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Command::Previous => "previous",
+			Command::Next => "next",
+			Command::Confirm => "confirm",
+			Command::Cancel => "cancel",
+			Command::EndTurn => "end turn",
+			Command::Undo => "undo",
+			Command::Pause => "pause",
+		}
+	}
+
+	/// The name used in the settings file, without spaces.
+	pub fn id(self) -> &'static str {
+		match self {
+			Command::EndTurn => "end_turn",
+			command => command.name(),
+		}
+	}
+
+	pub fn from_id(id: &str) -> Option<Command> {
+		Command::ALL.into_iter().find(|command| command.id() == id)
+	}
+
+	pub fn from_gamepad_button(button: Button) -> Option<Command> {
+		match button {
+			Button::DPadLeft | Button::DPadUp => Some(Command::Previous),
+			Button::DPadRight | Button::DPadDown => Some(Command::Next),
+			Button::South => Some(Command::Confirm),
+			Button::East => Some(Command::Cancel),
+			Button::North => Some(Command::EndTurn),
+			Button::West => Some(Command::Undo),
+			Button::Start => Some(Command::Pause),
+			_ => None,
+		}
+	}
+}
+
+/// The keys that can be bound to commands, a command can have several keys.
+#[derive(Clone)]
+pub struct KeyBindings {
+	bindings: Vec<(Command, KeyCode)>,
+}
+
+impl Default for KeyBindings {
+	fn default() -> KeyBindings {
+		KeyBindings {
+			bindings: vec![
+				(Command::Previous, KeyCode::Left),
+				(Command::Previous, KeyCode::Up),
+				(Command::Next, KeyCode::Right),
+				(Command::Next, KeyCode::Down),
+				(Command::Confirm, KeyCode::Return),
+				(Command::Confirm, KeyCode::Space),
+				(Command::Cancel, KeyCode::Back),
+				(Command::EndTurn, KeyCode::E),
+				(Command::Undo, KeyCode::Z),
+				(Command::Pause, KeyCode::Escape),
+			],
+		}
+	}
+}
+
+impl KeyBindings {
+	pub fn command(&self, key: KeyCode) -> Option<Command> {
+		self.bindings
+			.iter()
+			.find(|(_command, bound_key)| *bound_key == key)
+			.map(|(command, _key)| *command)
+	}
+
+	pub fn keys(&self, command: Command) -> impl Iterator<Item = KeyCode> + '_ {
+		self.bindings
+			.iter()
+			.filter(move |(bound_command, _key)| *bound_command == command)
+			.map(|(_command, key)| *key)
+	}
+
+	/// Makes the given key the only key of the command. The key is taken away from the command
+	/// it was bound to, if any, and if that was the only key of that other command, it gets
+	/// the previous key of the given command instead (so that no command is left without
+	/// a key, which could make the settings menu impossible to use).
+	pub fn rebind(&mut self, command: Command, key: KeyCode) {
+		let previous_key = self.keys(command).find(|previous_key| *previous_key != key);
+		let other_command = self.command(key).filter(|other_command| *other_command != command);
+		if let Some(other_command) =
+			other_command.filter(|other_command| self.keys(*other_command).count() == 1)
+		{
+			let Some(previous_key) = previous_key else {
+				// Nothing to swap with, the binding stays as it is.
+				return;
+			};
+			self.bindings.retain(|(_command, bound_key)| *bound_key != previous_key);
+			self.bindings.push((other_command, previous_key));
+		}
+		self.bindings
+			.retain(|(bound_command, bound_key)| *bound_command != command && *bound_key != key);
+		self.bindings.push((command, key));
+	}
+
+	/// Adds a key to a command, used when loading the settings.
+	pub fn add(&mut self, command: Command, key: KeyCode) {
+		self.bindings.retain(|(_command, bound_key)| *bound_key != key);
+		self.bindings.push((command, key));
+	}
+
+	pub fn clear(&mut self) {
+		self.bindings.clear();
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (Command, KeyCode)> + '_ {
+		self.bindings.iter().copied()
+	}
+}
+
+/// The name of a key, for the settings menu and file.
+pub fn key_name(key: KeyCode) -> String {
+	format!("{key:?}")
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+	BINDABLE_KEYS.into_iter().find(|key| key_name(*key) == name)
+}
+
+/// Keys that can be saved in the settings file. Other keys can still be bound
+/// but the binding is lost when the game is restarted.
+const BINDABLE_KEYS: [KeyCode; 50] = [
+	KeyCode::A,
+	KeyCode::B,
+	KeyCode::C,
+	KeyCode::D,
+	KeyCode::E,
+	KeyCode::F,
+	KeyCode::G,
+	KeyCode::H,
+	KeyCode::I,
+	KeyCode::J,
+	KeyCode::K,
+	KeyCode::L,
+	KeyCode::M,
+	KeyCode::N,
+	KeyCode::O,
+	KeyCode::P,
+	KeyCode::Q,
+	KeyCode::R,
+	KeyCode::S,
+	KeyCode::T,
+	KeyCode::U,
+	KeyCode::V,
+	KeyCode::W,
+	KeyCode::X,
+	KeyCode::Y,
+	KeyCode::Z,
+	KeyCode::Key0,
+	KeyCode::Key1,
+	KeyCode::Key2,
+	KeyCode::Key3,
+	KeyCode::Key4,
+	KeyCode::Key5,
+	KeyCode::Key6,
+	KeyCode::Key7,
+	KeyCode::Key8,
+	KeyCode::Key9,
+	KeyCode::Left,
+	KeyCode::Right,
+	KeyCode::Up,
+	KeyCode::Down,
+	KeyCode::Return,
+	KeyCode::Space,
+	KeyCode::Back,
+	KeyCode::Escape,
+	KeyCode::Tab,
+	KeyCode::Delete,
+	KeyCode::LShift,
+	KeyCode::RShift,
+	KeyCode::LControl,
+	KeyCode::RControl,
+];
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn rebinding_replaces_the_keys_of_the_command() {
+		let mut bindings = KeyBindings::default();
+		bindings.rebind(Command::Next, KeyCode::N);
+		assert!(bindings.keys(Command::Next).eq([KeyCode::N]));
+		assert!(bindings.command(KeyCode::Right).is_none());
+	}
+
+	#[test]
+	fn rebinding_takes_the_key_from_a_command_that_has_others() {
+		let mut bindings = KeyBindings::default();
+		bindings.rebind(Command::EndTurn, KeyCode::Space);
+		assert!(bindings.keys(Command::EndTurn).eq([KeyCode::Space]));
+		assert!(bindings.keys(Command::Confirm).eq([KeyCode::Return]));
+		assert!(bindings.command(KeyCode::E).is_none());
+	}
+
+	#[test]
+	fn rebinding_swaps_keys_rather_than_leave_a_command_without_key() {
+		let mut bindings = KeyBindings::default();
+		bindings.rebind(Command::EndTurn, KeyCode::Z);
+		assert!(bindings.keys(Command::EndTurn).eq([KeyCode::Z]));
+		assert!(bindings.keys(Command::Undo).eq([KeyCode::E]));
+		for command in Command::ALL {
+			assert!(bindings.keys(command).next().is_some(), "{}", command.name());
+		}
+	}
+
+	#[test]
+	fn rebinding_is_refused_when_there_is_nothing_to_swap() {
+		let mut bindings = KeyBindings::default();
+		bindings.clear();
+		bindings.add(Command::Confirm, KeyCode::Return);
+		bindings.rebind(Command::EndTurn, KeyCode::Return);
+		assert!(bindings.keys(Command::Confirm).eq([KeyCode::Return]));
+		assert!(bindings.keys(Command::EndTurn).next().is_none());
+	}
+}
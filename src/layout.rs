@@ -0,0 +1,115 @@
+//! Where the different parts of the interface go on the screen, computed from
+//! the window size (and DPI scale) instead of being hard-coded.
+
+use ggez::graphics::Rect;
+
+use crate::CardSpec;
+
+/// The smallest window size that the layout supports, the window cannot be resized
+/// to be smaller than that.
+pub const MIN_WINDOW_SIZE: (f32, f32) = (640.0, 480.0);
+
+/// Number of cards that the hand region is made to hold without overlapping.
+const HAND_CAPACITY: f32 = 5.0;
+
+/// Heights (at scale 1) of the regions, from the top of the screen to the bottom.
+const TOP_HUD_HEIGHT: f32 = 50.0;
+/// Room for the statuses above the creature cards and for the insertion slots below them.
+const BATTLEFIELD_HEIGHT: f32 = 80.0 + CardSpec::DIMS.1 + 70.0;
+const HAND_HEIGHT: f32 = CardSpec::DIMS.1 + 20.0;
+const BOTTOM_HUD_HEIGHT: f32 = 110.0;
+const TOTAL_HEIGHT: f32 = TOP_HUD_HEIGHT + BATTLEFIELD_HEIGHT + HAND_HEIGHT + BOTTOM_HUD_HEIGHT;
+
+/// Space to keep between the interface and the sides of the screen, at scale 1.
+const MARGIN: f32 = 20.0;
+
+/// Width (at scale 1) of the combat log panel, on the right of the battlefield.
+const LOG_PANEL_WIDTH: f32 = 300.0;
+
+/// The regions of the screen, for the current window size.
+pub struct Layout {
+	/// Scale of the whole interface. It follows the DPI scale of the screen but can get
+	/// smaller so that everything fits in small windows.
+	pub scale: f32,
+	/// The day/night clock.
+	pub top_hud: Rect,
+	/// The battlefield line, with some room above and below the creature cards.
+	pub battlefield: Rect,
+	/// The combat log, next to the top HUD and the battlefield (that are made narrower
+	/// to leave room for it).
+	pub log_panel: Rect,
+	pub hand: Rect,
+	/// The deck and roster indicator, and the piles.
+	pub bottom_hud: Rect,
+	pub end_turn_button: Rect,
+	/// Next to the end turn button.
+	pub undo_button: Rect,
+}
+
+impl Layout {
+	pub fn new(window_size: (f32, f32), dpi_scale: f32) -> Layout {
+		let (width, height) = (
+			window_size.0.max(MIN_WINDOW_SIZE.0),
+			window_size.1.max(MIN_WINDOW_SIZE.1),
+		);
+		let hand_width = (CardSpec::DIMS.0 + 10.0) * HAND_CAPACITY;
+		let scale = dpi_scale
+			.min(height / TOTAL_HEIGHT)
+			.min((width - MARGIN * 2.0) / hand_width);
+
+		let margin = MARGIN * scale;
+		let inner_width = width - margin * 2.0;
+		let mut y = 0.0;
+		let mut next_region = |region_height: f32| {
+			let region = Rect::new(margin, y, inner_width, region_height * scale);
+			y += region_height * scale;
+			region
+		};
+		let mut top_hud = next_region(TOP_HUD_HEIGHT);
+		let mut battlefield = next_region(BATTLEFIELD_HEIGHT);
+		let hand = next_region(HAND_HEIGHT);
+
+		let log_panel_width = LOG_PANEL_WIDTH * scale;
+		let log_panel = Rect::new(
+			top_hud.right() - log_panel_width,
+			top_hud.top() + margin / 2.0,
+			log_panel_width,
+			battlefield.bottom() - top_hud.top() - margin / 2.0,
+		);
+		top_hud.w -= log_panel_width + margin;
+		battlefield.w -= log_panel_width + margin;
+		// The bottom HUD takes whatever space is left.
+		let bottom_hud = Rect::new(margin, y, inner_width, height - y);
+
+		let button_dims = (150.0 * scale, 60.0 * scale);
+		let end_turn_button = Rect::new(
+			bottom_hud.right() - button_dims.0,
+			bottom_hud.bottom() - button_dims.1 - margin,
+			button_dims.0,
+			button_dims.1,
+		);
+		let undo_button_width = 110.0 * scale;
+		let undo_button = Rect::new(
+			end_turn_button.left() - undo_button_width - margin / 2.0,
+			end_turn_button.top(),
+			undo_button_width,
+			button_dims.1,
+		);
+
+		Layout {
+			scale,
+			top_hud,
+			battlefield,
+			log_panel,
+			hand,
+			bottom_hud,
+			end_turn_button,
+			undo_button,
+		}
+	}
+
+	/// The y coordinate of the top of the creature cards on the battlefield.
+	pub fn battlefield_cards_top(&self) -> f32 {
+		self.battlefield.top() + 80.0 * self.scale
+	}
+}
@@ -1,23 +1,360 @@
+mod audio;
+mod clock;
+mod combat_log;
+mod cycle_view;
+mod game_data;
+mod hot_reload;
+mod input;
+mod layout;
+mod menus;
+mod procedural_art;
+mod rich_text;
+mod scene;
+mod settings;
+mod spritesheet;
+mod toasts;
+mod tween;
+mod visual_effects;
+
+use std::collections::VecDeque;
 use std::time::Duration;
-use std::time::Instant;
 
 use ggez::glam::*;
-use ggez::graphics::{self, Canvas, Color, DrawMode, DrawParam, Image, Mesh, Rect, Text};
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, Rect, Text};
+use audio::{Audio, Music, SoundEffect};
+use clock::GameClock;
+use combat_log::{CombatLog, LogPanel};
+use cycle_view::CycleViews;
+use game_data::GameData;
 use ggez::{Context, GameResult};
+use hot_reload::HotReload;
+use input::Command;
+use layout::Layout;
+use menus::{GameOverScene, PauseScene, TitleScene, VictoryScene};
+use procedural_art::ProceduralArt;
+use rand::seq::SliceRandom;
+use rich_text::RichText;
+use scene::{Scene, SceneStack, Shared, Transition};
+use settings::Settings;
+use spritesheet::{Pose, Spritesheet};
+use toasts::{ToastKind, Toasts};
+use tween::{Easing, Tween};
+use visual_effects::VisualEffects;
 
+#[derive(Clone, PartialEq)]
 enum Action {
 	Null,
+	/// Deals damages to the enemy at the front.
 	Attack(u32),
+	/// Gains block points, that absorb damages before hp do.
+	Defend(u32),
+	/// Heals the most wounded ally (which may be the creature itself).
+	Heal(u32),
+	/// Gives food to the adjacent creature in front, which is an enemy if this creature
+	/// is itself at the front.
+	Feed(u32),
+	/// Places a new creature (usually a token that cannot be found in the deck)
+	/// at the back of the creature's side.
+	Summon(CardSpec),
+	/// Gives stacks of a status to the creature itself.
+	Buff(Status, u32),
+	/// Lowers the anger of every enemy.
+	Soothe(u32),
+	/// Takes up to that many stacks of a status from the enemy at the front
+	/// and gives them to the creature itself.
+	Steal(Status, u32),
+	/// A small script of actions and conditions, for actions that would take
+	/// a few sentences to explain.
+	Complex(Script),
+}
+
+impl Action {
+	/// The short text that fits in the action box.
+	fn label(&self) -> String {
+		match self {
+			Action::Null => "n".to_string(),
+			Action::Attack(damages) => format!("att {damages}"),
+			Action::Defend(block) => format!("def {block}"),
+			Action::Heal(heal) => format!("heal {heal}"),
+			Action::Feed(food) => format!("feed {food}"),
+			Action::Summon(_card_spec) => "summon".to_string(),
+			Action::Buff(status, stacks) => format!("+{stacks} {}", status.name()),
+			Action::Soothe(anger) => format!("soothe {anger}"),
+			Action::Steal(status, stacks) => format!("steal {stacks} {}", status.name()),
+			Action::Complex(script) => script.summary(),
+		}
+	}
+
+	fn color(&self) -> Color {
+		match self {
+			Action::Null => Color::WHITE,
+			Action::Attack(_) => Color::from_rgb(255, 200, 150),
+			Action::Defend(_) => Color::from_rgb(170, 190, 220),
+			Action::Heal(_) => Color::from_rgb(150, 255, 150),
+			Action::Feed(_) => Color::from_rgb(255, 200, 140),
+			Action::Summon(_) => Color::from_rgb(200, 160, 255),
+			Action::Soothe(_) => Color::from_rgb(255, 180, 220),
+			Action::Buff(status, _) | Action::Steal(status, _) => status.color(),
+			Action::Complex(_) => Color::from_rgb(255, 255, 180),
+		}
+	}
+
+	/// The full text that explains what the action does, in rich text markup.
+	fn description(&self) -> String {
+		match self {
+			Action::Null => "do nothing".to_string(),
+			Action::Attack(damages) => format!("attack [attack]{damages}[/]"),
+			Action::Defend(block) => format!("gain [block]{block} block[/]"),
+			Action::Heal(heal) => format!("heal the most wounded ally by [heal]{heal}[/]"),
+			Action::Feed(food) => format!("give {food} {{food}} to the creature in front"),
+			Action::Summon(card_spec) => format!("summon a {}", card_spec.name()),
+			Action::Buff(status, stacks) => format!("gain {stacks} {}", status.icon()),
+			Action::Soothe(anger) => format!("all enemies lose {anger} {{anger}}"),
+			Action::Steal(status, stacks) => {
+				format!("steal {stacks} {} from the foe in front", status.icon())
+			},
+			Action::Complex(script) => script.description(),
+		}
+	}
+}
+
+/// The steps of an `Action::Complex`, done in order.
+///
+/// Any combination of primitive actions and conditions can be expressed with this,
+/// so that new complex behaviors can be described as data instead of new `Action` variants.
+#[derive(Clone, PartialEq)]
+struct Script {
+	steps: Vec<ScriptStep>,
+}
+
+#[derive(Clone, PartialEq)]
+enum ScriptStep {
+	Do(Action),
+	If { condition: Condition, then: Vec<ScriptStep>, otherwise: Vec<ScriptStep> },
+}
+
+/// A condition that is evaluated from the point of view of the acting creature.
+#[derive(Clone, PartialEq)]
+enum Condition {
+	/// The creature has at least that many stacks of the status.
+	Has(Status, u32),
+	/// The creature is the closest to the enemies on its side.
+	IsAtFront,
+}
+
+impl Condition {
+	fn label(&self) -> String {
+		match self {
+			Condition::Has(status, 1) => status.name().to_string(),
+			Condition::Has(status, stacks) => format!("{stacks} {}", status.name()),
+			Condition::IsAtFront => "front".to_string(),
+		}
+	}
+
+	fn description(&self) -> String {
+		match self {
+			Condition::Has(status, stacks) => {
+				format!("this creature has at least {stacks} {}", status.icon())
+			},
+			Condition::IsAtFront => "this creature is at the front".to_string(),
+		}
+	}
+}
+
+/// What a creature listens to in order to react with a `Reaction`.
+#[derive(Clone, Copy)]
+enum Trigger {
+	/// This creature got damaged.
+	WhenHit,
+	/// This creature got some food.
+	WhenFed,
+	/// An ally of this creature fled the battlefield.
+	WhenAllyFlees,
+	/// This creature just became a friend.
+	WhenBefriended,
+	/// The player played a card from their hand.
+	WhenCardPlayed,
+	AtTurnEnd,
+}
+
+impl Trigger {
+	fn label(self) -> &'static str {
+		match self {
+			Trigger::WhenHit => "when hit",
+			Trigger::WhenFed => "when fed",
+			Trigger::WhenAllyFlees => "ally flees",
+			Trigger::WhenBefriended => "befriended",
+			Trigger::WhenCardPlayed => "card played",
+			Trigger::AtTurnEnd => "turn end",
+		}
+	}
+
+	fn description(self) -> &'static str {
+		match self {
+			Trigger::WhenHit => "when this creature is hit",
+			Trigger::WhenFed => "when this creature gets food",
+			Trigger::WhenAllyFlees => "when an ally flees",
+			Trigger::WhenBefriended => "when this creature becomes a friend",
+			Trigger::WhenCardPlayed => "when a card is played",
+			Trigger::AtTurnEnd => "at the end of each turn",
+		}
+	}
+
+	/// Does the given event concern a creature that listens to this trigger?
+	fn is_triggered_by(self, event: &BattleEvent, listener: CreatureId, is_friend: bool) -> bool {
+		match (self, event) {
+			(Trigger::WhenHit, BattleEvent::Damaged { creature, .. }) => *creature == listener,
+			(Trigger::WhenFed, BattleEvent::Fed { creature }) => *creature == listener,
+			(Trigger::WhenAllyFlees, BattleEvent::Fled { was_friend, .. }) => {
+				*was_friend == is_friend
+			},
+			(Trigger::WhenBefriended, BattleEvent::Befriended { creature }) => {
+				*creature == listener
+			},
+			(Trigger::WhenCardPlayed, BattleEvent::CardPlayed) => true,
+			(Trigger::AtTurnEnd, BattleEvent::TurnEnd) => true,
+			_ => false,
+		}
+	}
+}
+
+/// A passive effect of a creature, the action is done whenever the trigger is triggered.
+#[derive(Clone)]
+struct Reaction {
+	trigger: Trigger,
+	action: Action,
+}
+
+/// Something that happened during a battle, that creatures might react to.
+///
+/// Events refer to creatures by their ID, so they still make sense after creatures
+/// moved around (or even left the battlefield).
+#[derive(Clone)]
+enum BattleEvent {
+	TurnStart,
+	TurnEnd,
+	CardPlayed,
+	/// The creature lost that many hp, after its block absorbed some damages.
+	Damaged { creature: CreatureId, attacker: CreatureId, hp_lost: u32, blocked: u32 },
+	Healed { creature: CreatureId, hp_gained: u32 },
+	Fed { creature: CreatureId },
+	/// The fled creature is not on the battlefield anymore.
+	Fled { creature: CreatureId, was_friend: bool },
+	/// A foe's anger got down to 0, it is going to become a friend.
+	Calmed { creature: CreatureId },
+	Befriended { creature: CreatureId },
+}
+
+impl Script {
+	/// Action boxes are small, summaries longer than that get cut.
+	const SUMMARY_MAX_LEN: usize = 9;
+
+	fn steps_label(steps: &[ScriptStep]) -> String {
+		let labels: Vec<String> = steps
+			.iter()
+			.map(|step| match step {
+				ScriptStep::Do(action) => action.label(),
+				ScriptStep::If { condition, then, otherwise } if otherwise.is_empty() => {
+					format!("{}?{}", condition.label(), Script::steps_label(then))
+				},
+				ScriptStep::If { condition, then, otherwise } => format!(
+					"{}?{}:{}",
+					condition.label(),
+					Script::steps_label(then),
+					Script::steps_label(otherwise)
+				),
+			})
+			.collect();
+		labels.join(",")
+	}
+
+	fn steps_description(steps: &[ScriptStep]) -> String {
+		let descriptions: Vec<String> = steps
+			.iter()
+			.map(|step| match step {
+				ScriptStep::Do(action) => action.description(),
+				ScriptStep::If { condition, then, otherwise } if otherwise.is_empty() => format!(
+					"if {}, {}",
+					condition.description(),
+					Script::steps_description(then)
+				),
+				ScriptStep::If { condition, then, otherwise } => format!(
+					"if {}, {}, otherwise {}",
+					condition.description(),
+					Script::steps_description(then),
+					Script::steps_description(otherwise)
+				),
+			})
+			.collect();
+		descriptions.join(", then ")
+	}
+
+	fn summary(&self) -> String {
+		let label = Script::steps_label(&self.steps);
+		if label.chars().count() > Script::SUMMARY_MAX_LEN {
+			let cut: String = label.chars().take(Script::SUMMARY_MAX_LEN - 1).collect();
+			format!("{cut}…")
+		} else {
+			label
+		}
+	}
+
+	fn description(&self) -> String {
+		Script::steps_description(&self.steps)
+	}
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Status {
+	Food,
+	Scared,
+	Scary,
+	Night,
+	Anger,
 }
 
+impl Status {
+	fn name(self) -> &'static str {
+		match self {
+			Status::Food => "food",
+			Status::Scared => "scared",
+			Status::Scary => "scary",
+			Status::Night => "night",
+			Status::Anger => "anger",
+		}
+	}
+
+	/// The inline icon of this status, in rich text markup.
+	fn icon(self) -> String {
+		format!("{{{}}}", self.name())
+	}
+
+	/// The color of the stack counter of this status.
+	fn color(self) -> Color {
+		match self {
+			Status::Food => Color::from_rgb(255, 200, 140),
+			Status::Scared => Color::from_rgb(150, 200, 255),
+			Status::Scary => Color::from_rgb(230, 120, 255),
+			Status::Night => Color::from_rgb(255, 240, 170),
+			Status::Anger => Color::from_rgb(255, 90, 90),
+		}
+	}
+}
+
+#[derive(Clone)]
 struct Cycle {
 	actions: Vec<Action>,
+	/// How many times the cycle turned (the top action going to the bottom),
+	/// for the interface to animate it.
+	rotations: u32,
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 enum CardSpec {
 	Fwog,
 	DragonFly,
+	/// A token summoned by fwogs, not to be found in the deck.
+	Tadpole,
 	Food,
 }
 
@@ -25,41 +362,111 @@ struct CardDrawingParams {
 	hovered: bool,
 	selected: bool,
 	targetable: bool,
+	pose: Pose,
+	/// How far the animation of the sprite is.
+	animation_time: Duration,
 }
 
 impl CardSpec {
-	fn is_creature(&self) -> bool {
+	const ALL: [CardSpec; 4] =
+		[CardSpec::Fwog, CardSpec::DragonFly, CardSpec::Tadpole, CardSpec::Food];
+
+	fn from_id(id: &str) -> Option<CardSpec> {
+		CardSpec::ALL.into_iter().find(|card_spec| card_spec.id() == id)
+	}
+
+	fn name(&self) -> &'static str {
 		match self {
-			CardSpec::Fwog | CardSpec::DragonFly => true,
-			CardSpec::Food => false,
+			CardSpec::Fwog => "fwog",
+			CardSpec::DragonFly => "dragon fly",
+			CardSpec::Tadpole => "tadpole",
+			CardSpec::Food => "food!",
 		}
 	}
 
-	fn instanciate_to_creature(&self) -> Option<Creature> {
+	/// Tokens are creatures that do not come from a card, and thus do not go
+	/// to the discard pile when they flee.
+	fn is_token(&self) -> bool {
+		matches!(self, CardSpec::Tadpole)
+	}
+
+	fn is_creature(&self) -> bool {
 		match self {
-			CardSpec::Fwog => Some(Creature {
-				card_spec: self.clone(),
-				food: 0,
-				hp: 4,
-				cycle: Cycle { actions: vec![Action::Null, Action::Attack(1)] },
-			}),
-			CardSpec::DragonFly => Some(Creature {
-				card_spec: self.clone(),
-				food: 0,
-				hp: 4,
-				cycle: Cycle { actions: vec![Action::Null, Action::Null, Action::Attack(2)] },
-			}),
-			_ => None,
+			CardSpec::Fwog | CardSpec::DragonFly | CardSpec::Tadpole => true,
+			CardSpec::Food => false,
 		}
 	}
 
+	/// The stats of the creature come from the game data, its actions and reactions
+	/// are described here.
+	fn instanciate_to_creature(&self, id: CreatureId, data: &GameData) -> Option<Creature> {
+		let stats = data.creature_stats(self)?;
+		let (actions, reactions) = match self {
+			CardSpec::Fwog => (
+				vec![
+					Action::Null,
+					Action::Attack(1),
+					Action::Defend(2),
+					Action::Null,
+					Action::Summon(CardSpec::Tadpole),
+				],
+				vec![Reaction { trigger: Trigger::WhenHit, action: Action::Buff(Status::Food, 1) }],
+			),
+			CardSpec::DragonFly => (
+				vec![
+					Action::Null,
+					Action::Null,
+					Action::Attack(2),
+					Action::Heal(1),
+					Action::Complex(Script {
+						steps: vec![ScriptStep::If {
+							condition: Condition::Has(Status::Food, 1),
+							then: vec![ScriptStep::Do(Action::Attack(3))],
+							otherwise: vec![ScriptStep::Do(Action::Steal(Status::Food, 1))],
+						}],
+					}),
+				],
+				vec![
+					Reaction { trigger: Trigger::WhenAllyFlees, action: Action::Attack(2) },
+					Reaction { trigger: Trigger::WhenBefriended, action: Action::Soothe(1) },
+					Reaction { trigger: Trigger::WhenCardPlayed, action: Action::Defend(1) },
+				],
+			),
+			CardSpec::Tadpole => (
+				vec![
+					Action::Null,
+					Action::Complex(Script {
+						steps: vec![ScriptStep::If {
+							condition: Condition::IsAtFront,
+							then: vec![ScriptStep::Do(Action::Defend(1))],
+							otherwise: vec![ScriptStep::Do(Action::Feed(1))],
+						}],
+					}),
+					Action::Buff(Status::Food, 1),
+				],
+				vec![
+					Reaction { trigger: Trigger::WhenFed, action: Action::Heal(1) },
+					Reaction { trigger: Trigger::AtTurnEnd, action: Action::Defend(1) },
+				],
+			),
+			CardSpec::Food => return None,
+		};
+		Some(Creature {
+			anger: stats.anger,
+			scary: stats.scary,
+			reactions,
+			..Creature::new(self.clone(), id, stats.hp, actions)
+		})
+	}
+
 	const DIMS: (f32, f32) = (200.0, 250.0);
 
 	fn draw(
 		&self,
 		ctx: &mut Context,
 		canvas: &mut Canvas,
-		spritesheet: &Image,
+		spritesheet: &Spritesheet,
+		data: &GameData,
 		dst: Vec2,
 		params: CardDrawingParams,
 	) -> GameResult {
@@ -88,64 +495,831 @@ impl CardSpec {
 			canvas.draw(&rectangle, Vec2::new(0.0, 0.0));
 		}
 
-		let (name, sprite) = match self {
-			CardSpec::Fwog => ("fwog", Rect::new(0.0, 0.2, 0.5, 0.4)),
-			CardSpec::DragonFly => ("dragon fly", Rect::new(0.5, 0.12, 0.5, 0.45)),
-			CardSpec::Food => ("food!", Rect::new(0.0, 0.62, 0.5, 0.38)),
-		};
-
 		canvas.draw(
-			Text::new(name).set_scale(26.0),
+			Text::new(self.name()).set_scale(26.0),
 			DrawParam::from(Vec2::new(dst.x + 10.0, dst.y + 10.0)).color(Color::WHITE),
 		);
-		canvas.draw(
-			spritesheet,
-			DrawParam::default()
-				.dest(Vec2::new(dst.x + 10.0, dst.y + 30.0 + 10.0))
-				.scale(Vec2::new(0.1, 0.1))
-				.src(sprite),
-		);
+		// The art is centered in a 100x90 area below the name.
+		let art_center = Vec2::new(dst.x + 10.0 + 50.0, dst.y + 40.0 + 45.0);
+		let procedural_art = data.art(self).or_else(|| {
+			let has_sprite = spritesheet.has_creature_sprite(self.id());
+			(!has_sprite).then(|| ProceduralArt::from_seed(self.id()))
+		});
+		let (pose, time) = (params.pose, params.animation_time);
+		match procedural_art {
+			Some(art) => art.draw(ctx, canvas, art_center, 38.0, pose, time)?,
+			None => spritesheet.draw_posed(ctx, canvas, self.id(), pose, time, art_center)?,
+		}
 
-		match self {
-			CardSpec::Food => {
-				canvas.draw(
-					Text::new("apply 2 food").set_scale(20.0),
-					DrawParam::from(Vec2::new(dst.x + 10.0, dst.y + 175.0)).color(Color::WHITE),
-				);
-			},
-			CardSpec::Fwog => {},
-			CardSpec::DragonFly => {},
+		if let Some(rules_text) = self.rules_text() {
+			let text = RichText::new(ctx, rules_text, 20.0, CardSpec::DIMS.0 - 20.0)?;
+			let pos = Vec2::new(dst.x + 10.0, dst.y + 175.0);
+			text.draw(ctx, canvas, spritesheet, pos, Color::WHITE)?;
 		}
 
 		Ok(())
 	}
+
+	/// The name of the card in the game data, and of its sprite in the spritesheet atlas
+	/// (the food sprite is also used as the food icon).
+	fn id(&self) -> &'static str {
+		match self {
+			CardSpec::Fwog => "fwog",
+			CardSpec::DragonFly => "dragon_fly",
+			CardSpec::Tadpole => "tadpole",
+			CardSpec::Food => "food",
+		}
+	}
+
+	/// What the card does when played, in rich text markup (creature cards just summon
+	/// their creature, they have no rules text).
+	fn rules_text(&self) -> Option<&'static str> {
+		match self {
+			CardSpec::Food => Some("apply 2 {food}"),
+			CardSpec::Fwog => None,
+			CardSpec::DragonFly => None,
+			CardSpec::Tadpole => None,
+		}
+	}
 }
 
+/// Identifies a creature on the battlefield, for as long as it stays there
+/// (unlike its position, that changes when other creatures come and go).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct CreatureId(u32);
+
+#[derive(Clone)]
 struct Creature {
+	id: CreatureId,
 	card_spec: CardSpec,
 	food: u32,
+	/// While a creature has scared stacks, it is too scared to attack and skips its
+	/// `Action::Attack` actions. It loses one stack every time it acts.
+	scared: u32,
+	/// When a scary creature does something (a non-null action), it puts that many
+	/// scared stacks on each of the enemies that are adjacent to it on the battlefield.
+	scary: u32,
+	/// Each night stack makes the next non-null action of the creature be skipped
+	/// as if it was a `Action::Null`, consuming the stack.
+	night: u32,
+	/// Block points absorb damages before hp do.
+	block: u32,
+	/// Foes are angry, that is why they fight. A foe whose anger gets down to 0 asks
+	/// to become friends, and joins the friends at the front.
+	anger: u32,
 	hp: i32,
+	max_hp: i32,
 	cycle: Cycle,
+	/// Passive effects, each done in response to some kind of battle event.
+	reactions: Vec<Reaction>,
 }
 
+impl Creature {
+	fn new(card_spec: CardSpec, id: CreatureId, hp: i32, actions: Vec<Action>) -> Creature {
+		Creature {
+			id,
+			card_spec,
+			food: 0,
+			scared: 0,
+			scary: 0,
+			night: 0,
+			block: 0,
+			anger: 0,
+			hp,
+			max_hp: hp,
+			cycle: Cycle { actions, rotations: 0 },
+			reactions: vec![],
+		}
+	}
+
+	fn status(&self, status: Status) -> u32 {
+		match status {
+			Status::Food => self.food,
+			Status::Scared => self.scared,
+			Status::Scary => self.scary,
+			Status::Night => self.night,
+			Status::Anger => self.anger,
+		}
+	}
+
+	fn status_mut(&mut self, status: Status) -> &mut u32 {
+		match status {
+			Status::Food => &mut self.food,
+			Status::Scared => &mut self.scared,
+			Status::Scary => &mut self.scary,
+			Status::Night => &mut self.night,
+			Status::Anger => &mut self.anger,
+		}
+	}
+}
+
+/// Cloned before each card play, so that plays can be undone.
+#[derive(Clone)]
 struct Battlefield {
+	/// Where the stats of the creatures come from.
+	data: GameData,
 	friends: Vec<Creature>,
 	foes: Vec<Creature>,
+	/// Number of turns played since the start of the battle.
+	turn: u32,
+	/// Events that happened and that the creatures did not react to yet.
+	/// It is empty outside of `resolve_events`.
+	events: VecDeque<BattleEvent>,
+	/// Reactions to events that are yet to be done, by the creature that reacts.
+	/// It is empty outside of `resolve_events`.
+	reactions_to_do: VecDeque<(CreatureId, Action)>,
+	/// The creatures that are yet to act during the turn being played.
+	turn_order: VecDeque<CreatureId>,
+	/// The ID that the next creature to enter the battlefield will get.
+	next_creature_id: u32,
+	/// What happened since the start of the battle, in plain language.
+	log: CombatLog,
+	/// How many times the outcome of something was left to chance (like shuffling a pile).
+	/// Undoing a play that involved chance would allow to try again until lucky.
+	random_outcome_count: u32,
+	hand: Vec<Card>,
+	/// Cards to be drawn, the last one is at the top.
+	draw_pile: Vec<Card>,
+	/// Cards that were played or discarded (or whose creature fled), they get shuffled
+	/// back into the draw pile when it is empty.
+	discard_pile: Vec<Card>,
 }
 
+/// What a card from the hand can be played on.
+#[derive(Clone, Copy)]
+enum PlayTarget {
+	/// Creature cards are placed among the friends, at that index (0 is the front).
+	FriendInsertionSlot(usize),
+	/// Other cards are played on creatures.
+	Creature(CreatureId),
+}
+
+/// Why a card cannot be played (on some target).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum PlayError {
+	/// There is no card at that index in the hand.
+	NoSuchCard,
+	/// Only creature cards can be placed on the battlefield.
+	NotACreatureCard,
+	/// The creature has no stats in the game data, so it cannot be placed.
+	MissingCreatureData,
+	/// Creature cards are placed on the battlefield, not played on creatures.
+	NotPlayableOnCreatures,
+	/// The insertion slot does not exist.
+	TargetOutOfRange,
+	/// The targeted creature is not on the battlefield (anymore).
+	TargetGone,
+}
+
+impl PlayError {
+	fn message(self) -> &'static str {
+		match self {
+			PlayError::NoSuchCard => "that card is not in the hand",
+			PlayError::NotACreatureCard => "only creature cards go in the slots",
+			PlayError::MissingCreatureData => "this creature is missing from the card data",
+			PlayError::NotPlayableOnCreatures => "creature cards go in the slots between friends",
+			PlayError::TargetOutOfRange => "there is no such slot",
+			PlayError::TargetGone => "that creature is not there anymore",
+		}
+	}
+}
+
+/// Everything that happened because of a card being played, in order.
+type Effects = Vec<BattleEvent>;
+
+/// A part of a turn: what a creature did when it acted and everything that followed,
+/// or what happened at the start or at the end of the turn (without an actor).
+struct TurnStep {
+	actor: Option<CreatureId>,
+	effects: Effects,
+}
+
+/// At the end of each turn, every friend on the battlefield eats that much of its food.
+/// A friend that has not enough food to eat is hungry and too weak to fight, it gets
+/// a scared stack.
+///
+/// There is no limit to the number of friends, but every friend is a mouth to feed
+/// (and a creature card that dilutes the deck), so it may be better to keep the team small.
+const FRIEND_UPKEEP: u32 = 1;
+
+/// Reactions can trigger events that trigger reactions, and so on. To avoid infinite loops,
+/// the resolution of events stops after that many reactions (and the log tells the player,
+/// so that the reactions that did not happen are not a mystery).
+const MAX_REACTIONS_PER_RESOLUTION: usize = 100;
+
+/// A battle alternates between days of `DAY_TURNS` turns and nights of `NIGHT_TURNS` turns.
+const DAY_TURNS: u32 = 4;
+const NIGHT_TURNS: u32 = 2;
+
 impl Battlefield {
-	fn _get(&self, which_creature: WhichBattlefieldCreature) -> &Creature {
-		match which_creature {
-			WhichBattlefieldCreature::Friend(WhichBattlefieldFriend(i)) => &self.friends[i],
-			WhichBattlefieldCreature::Foe(WhichBattlefieldFoe(i)) => &self.foes[i],
+	fn new(
+		friends: Vec<CardSpec>,
+		foes: Vec<CardSpec>,
+		mut deck: Vec<Card>,
+		data: &GameData,
+	) -> Battlefield {
+		deck.shuffle(&mut rand::thread_rng());
+		let mut battlefield = Battlefield {
+			data: data.clone(),
+			friends: vec![],
+			foes: vec![],
+			turn: 0,
+			events: VecDeque::new(),
+			reactions_to_do: VecDeque::new(),
+			turn_order: VecDeque::new(),
+			next_creature_id: 0,
+			log: CombatLog::default(),
+			random_outcome_count: 0,
+			hand: vec![],
+			draw_pile: deck,
+			discard_pile: vec![],
+		};
+		for card_spec in friends {
+			let id = battlefield.new_creature_id();
+			battlefield.friends.extend(card_spec.instanciate_to_creature(id, data));
+		}
+		for card_spec in foes {
+			let id = battlefield.new_creature_id();
+			battlefield.foes.extend(card_spec.instanciate_to_creature(id, data));
+		}
+		battlefield.draw_hand();
+		battlefield
+	}
+
+	/// Gives an ID to a creature that enters the battlefield, it keeps it until it leaves
+	/// (even if it becomes a friend).
+	fn new_creature_id(&mut self) -> CreatureId {
+		let id = CreatureId(self.next_creature_id);
+		self.next_creature_id += 1;
+		id
+	}
+
+	const HAND_SIZE: usize = 5;
+
+	/// Draws cards until the hand is full (or there are no cards left to draw).
+	fn draw_hand(&mut self) {
+		while self.hand.len() < Battlefield::HAND_SIZE {
+			if self.draw_pile.is_empty() {
+				if self.discard_pile.is_empty() {
+					break;
+				}
+				self.draw_pile.append(&mut self.discard_pile);
+				self.draw_pile.shuffle(&mut rand::thread_rng());
+				self.random_outcome_count += 1;
+			}
+			self.hand.push(self.draw_pile.pop().unwrap());
+		}
+	}
+
+	/// All the cards of the player that are not on the battlefield
+	/// (the roster also counts the friends on the battlefield that came from a card).
+	fn deck(&self) -> impl Iterator<Item = &Card> {
+		self.hand.iter().chain(self.draw_pile.iter()).chain(self.discard_pile.iter())
+	}
+
+	/// Checks that the card at the given index in the hand can be played on the given target.
+	fn check_play(&self, hand_index: usize, target: PlayTarget) -> Result<(), PlayError> {
+		let card = self.hand.get(hand_index).ok_or(PlayError::NoSuchCard)?;
+		match target {
+			PlayTarget::FriendInsertionSlot(index) => {
+				if !card.card_spec.is_creature() {
+					return Err(PlayError::NotACreatureCard);
+				}
+				if self.data.creature_stats(&card.card_spec).is_none() {
+					return Err(PlayError::MissingCreatureData);
+				}
+				if index > self.friends.len() {
+					return Err(PlayError::TargetOutOfRange);
+				}
+			},
+			PlayTarget::Creature(id) => {
+				if card.card_spec.is_creature() {
+					return Err(PlayError::NotPlayableOnCreatures);
+				}
+				if !self.contains(id) {
+					return Err(PlayError::TargetGone);
+				}
+			},
 		}
+		Ok(())
 	}
 
-	fn get_mut(&mut self, which_creature: WhichBattlefieldCreature) -> &mut Creature {
-		match which_creature {
-			WhichBattlefieldCreature::Friend(WhichBattlefieldFriend(i)) => &mut self.friends[i],
-			WhichBattlefieldCreature::Foe(WhichBattlefieldFoe(i)) => &mut self.foes[i],
+	/// Plays the card at the given index in the hand on the given target, if it can be.
+	/// This is the only way the interface changes the battle, apart from ending the turn.
+	fn play_card(&mut self, hand_index: usize, target: PlayTarget) -> Result<Effects, PlayError> {
+		self.check_play(hand_index, target)?;
+		match target {
+			PlayTarget::FriendInsertionSlot(index) => {
+				// `check_play` made sure that the creature has its stats, so it can be made
+				// (and only then it gets an ID). The card stays with its creature while it is
+				// on the battlefield.
+				let id = self.new_creature_id();
+				let card_spec = &self.hand[hand_index].card_spec;
+				let creature = card_spec.instanciate_to_creature(id, &self.data).unwrap();
+				self.hand.remove(hand_index);
+				self.log.push(format!("{} joins the battle", creature.card_spec.name()), vec![id]);
+				self.friends.insert(index, creature);
+			},
+			PlayTarget::Creature(id) => {
+				let card = self.hand.remove(hand_index);
+				self.give_food(id, 2, None);
+				self.discard_pile.push(card);
+			},
 		}
+		self.events.push_back(BattleEvent::CardPlayed);
+		Ok(self.resolve_events())
+	}
+
+	/// Plays the turn, then the hand is discarded and a new hand is drawn.
+	fn end_turn(&mut self) -> Vec<TurnStep> {
+		let steps = self.play_turn();
+		self.discard_pile.append(&mut self.hand);
+		self.draw_hand();
+		steps
+	}
+
+	/// Where the given creature is, if it is still on the battlefield.
+	/// Positions change when creatures come and go, unlike IDs.
+	fn position(&self, id: CreatureId) -> Option<WhichBattlefieldCreature> {
+		if let Some(i) = self.friends.iter().position(|friend| friend.id == id) {
+			Some(WhichBattlefieldCreature::Friend(WhichBattlefieldFriend(i)))
+		} else {
+			let i = self.foes.iter().position(|foe| foe.id == id)?;
+			Some(WhichBattlefieldCreature::Foe(WhichBattlefieldFoe(i)))
+		}
+	}
+
+	fn contains(&self, id: CreatureId) -> bool {
+		self.position(id).is_some()
+	}
+
+	fn is_friend(&self, id: CreatureId) -> bool {
+		matches!(self.position(id), Some(WhichBattlefieldCreature::Friend(_)))
+	}
+
+	fn is_at_front(&self, id: CreatureId) -> bool {
+		matches!(
+			self.position(id),
+			Some(
+				WhichBattlefieldCreature::Friend(WhichBattlefieldFriend(0)) |
+					WhichBattlefieldCreature::Foe(WhichBattlefieldFoe(0))
+			)
+		)
+	}
+
+	/// Friends from the front to the back, and then foes from the front to the back.
+	fn all_creatures(&self) -> Vec<CreatureId> {
+		self.friends.iter().chain(self.foes.iter()).map(|creature| creature.id).collect()
+	}
+
+	/// The name of the creature, for the log.
+	fn name(&self, id: CreatureId) -> &'static str {
+		self.get(id).map_or("someone", |creature| creature.card_spec.name())
+	}
+
+	fn get(&self, id: CreatureId) -> Option<&Creature> {
+		self.friends.iter().chain(self.foes.iter()).find(|creature| creature.id == id)
+	}
+
+	fn get_mut(&mut self, id: CreatureId) -> Option<&mut Creature> {
+		self.friends.iter_mut().chain(self.foes.iter_mut()).find(|creature| creature.id == id)
+	}
+
+	/// The side of the given creature and the side of its enemies, both from the front
+	/// to the back. Both are empty if the creature is not on the battlefield.
+	fn sides(&self, id: CreatureId) -> (&[Creature], &[Creature]) {
+		match self.position(id) {
+			Some(WhichBattlefieldCreature::Friend(_)) => (&self.friends, &self.foes),
+			Some(WhichBattlefieldCreature::Foe(_)) => (&self.foes, &self.friends),
+			None => (&[], &[]),
+		}
+	}
+
+	/// The enemies of the given creature that are right next to it on the battlefield line,
+	/// which is only the enemy at the front if the creature is itself at the front.
+	fn adjacent_enemies(&self, id: CreatureId) -> Vec<CreatureId> {
+		if self.is_at_front(id) {
+			self.front_enemy(id).into_iter().collect()
+		} else {
+			vec![]
+		}
+	}
+
+	/// The enemy that is the closest to the given creature, the one that gets attacked.
+	fn front_enemy(&self, id: CreatureId) -> Option<CreatureId> {
+		let (_allies, enemies) = self.sides(id);
+		enemies.first().map(|enemy| enemy.id)
+	}
+
+	/// The creature that is right next to the given creature, on the side of the enemies.
+	/// It is an ally, or the enemy at the front if the given creature is at the front.
+	fn creature_in_front(&self, id: CreatureId) -> Option<CreatureId> {
+		let (allies, _enemies) = self.sides(id);
+		match allies.iter().position(|ally| ally.id == id) {
+			Some(i) if i >= 1 => Some(allies[i - 1].id),
+			_ => self.front_enemy(id),
+		}
+	}
+
+	/// The ally that misses the most hp (which may be the given creature itself),
+	/// the one at the front if there is a tie.
+	fn most_wounded_ally(&self, id: CreatureId) -> CreatureId {
+		let (allies, _enemies) = self.sides(id);
+		allies
+			.iter()
+			.rev()
+			.max_by_key(|ally| ally.max_hp - ally.hp)
+			.map_or(id, |ally| ally.id)
+	}
+
+	/// Damages are first absorbed by block points, and what remains is taken from the hp.
+	/// Hitting a foe also makes it angrier.
+	fn deal_damages(&mut self, attacker: CreatureId, target: CreatureId, damages: u32) {
+		let is_foe = !self.is_friend(target);
+		let (attacker_name, target_name) = (self.name(attacker), self.name(target));
+		let Some(creature) = self.get_mut(target) else {
+			return;
+		};
+		let absorbed = damages.min(creature.block);
+		let hp_before = creature.hp;
+		creature.block -= absorbed;
+		creature.hp -= (damages - absorbed) as i32;
+		if is_foe {
+			creature.anger += 1;
+		}
+		let hp_after = creature.hp;
+		let blocked = if absorbed >= 1 { format!("{absorbed} blocked, ") } else { String::new() };
+		self.log.push(
+			format!(
+				"{attacker_name} attacks {target_name} for [attack]{damages}[/] \
+				({blocked}{target_name} {hp_before}→{hp_after})"
+			),
+			vec![attacker, target],
+		);
+		self.events.push_back(BattleEvent::Damaged {
+			creature: target,
+			attacker,
+			hp_lost: damages - absorbed,
+			blocked: absorbed,
+		});
+		self.flee_if_defeated(target);
+	}
+
+	/// Food makes foes less angry, they may even become friends.
+	/// The food comes from a card, or from a creature (the giver).
+	fn give_food(&mut self, target: CreatureId, food: u32, giver: Option<CreatureId>) {
+		let target_name = self.name(target);
+		match giver {
+			Some(giver) => self.log.push(
+				format!("{} feeds {target_name} {food} {{food}}", self.name(giver)),
+				vec![giver, target],
+			),
+			None => self.log.push(format!("{target_name} receives {food} {{food}}"), vec![target]),
+		}
+		let Some(creature) = self.get_mut(target) else {
+			return;
+		};
+		creature.food += food;
+		self.events.push_back(BattleEvent::Fed { creature: target });
+		self.lower_anger(target, food);
+	}
+
+	fn lower_anger(&mut self, target: CreatureId, anger: u32) {
+		let Some(creature) = self.get_mut(target) else {
+			return;
+		};
+		let was_angry = creature.anger >= 1;
+		creature.anger = creature.anger.saturating_sub(anger);
+		if was_angry && creature.anger == 0 {
+			// Befriending moves creatures around, so it is not done right away
+			// but when events are resolved.
+			self.events.push_back(BattleEvent::Calmed { creature: target });
+		}
+	}
+
+	/// The given foe joins the friends, at the front (so that its position on the
+	/// battlefield line does not change if it was the foe at the front).
+	/// If it did not act yet during the turn being played, it waits for the next turn.
+	fn befriend(&mut self, id: CreatureId) {
+		if let Some(WhichBattlefieldCreature::Foe(WhichBattlefieldFoe(i))) = self.position(id) {
+			let creature = self.foes.remove(i);
+			self.friends.insert(0, creature);
+			self.turn_order.retain(|&other| other != id);
+			self.log.push(format!("{} becomes a friend", self.name(id)), vec![id]);
+			self.events.push_back(BattleEvent::Befriended { creature: id });
+		}
+	}
+
+	/// Makes every creature react (in order, friends from the front to the back and then
+	/// foes) to every pending event (in the order they happened), including the events
+	/// caused by the reactions themselves. Returns the events that were resolved.
+	///
+	/// Creatures that left the battlefield do not react anymore.
+	fn resolve_events(&mut self) -> Vec<BattleEvent> {
+		let mut resolved_events = vec![];
+		let mut reaction_count = 0;
+		loop {
+			if let Some((id, action)) = self.reactions_to_do.pop_front() {
+				if reaction_count >= MAX_REACTIONS_PER_RESOLUTION {
+					let text = "the dream settles, the remaining reactions fade away";
+					self.log.push(text.to_string(), vec![]);
+					self.reactions_to_do.clear();
+					self.events.clear();
+					break;
+				}
+				let Some(creature) = self.get(id) else {
+					continue;
+				};
+				reaction_count += 1;
+				let is_scared = creature.scared >= 1;
+				self.perform(id, &action, is_scared);
+			} else if let Some(event) = self.events.pop_front() {
+				resolved_events.push(event.clone());
+				if let BattleEvent::Calmed { creature } = event {
+					if self.get(creature).is_some_and(|creature| creature.anger == 0) {
+						self.befriend(creature);
+					}
+					continue;
+				}
+				let mut reactions_to_do = vec![];
+				for listener in self.friends.iter().chain(self.foes.iter()) {
+					let is_friend = self.is_friend(listener.id);
+					for reaction in listener.reactions.iter() {
+						if reaction.trigger.is_triggered_by(&event, listener.id, is_friend) {
+							reactions_to_do.push((listener.id, reaction.action.clone()));
+						}
+					}
+				}
+				self.reactions_to_do.extend(reactions_to_do);
+			} else {
+				break;
+			}
+		}
+		resolved_events
+	}
+
+	/// Makes the given creature do the action at the top of its cycle,
+	/// then sends that action to the bottom of the cycle.
+	fn creature_act(&mut self, id: CreatureId) {
+		let Some(creature) = self.get_mut(id) else {
+			return;
+		};
+		if creature.cycle.actions.is_empty() {
+			return;
+		}
+		let action = creature.cycle.actions.remove(0);
+		let is_scared = creature.scared >= 1;
+		creature.scared = creature.scared.saturating_sub(1);
+		let scary = creature.scary;
+		let is_sleepy = creature.night >= 1 && !matches!(action, Action::Null);
+		if is_sleepy {
+			creature.night -= 1;
+		}
+
+		let did_something = if is_sleepy {
+			// The night turns the action into a null action.
+			self.log.push(format!("{} is too sleepy to act", self.name(id)), vec![id]);
+			false
+		} else {
+			self.perform(id, &action, is_scared)
+		};
+
+		if did_something && scary >= 1 {
+			for enemy_id in self.adjacent_enemies(id) {
+				let text = format!("{} scares {}", self.name(id), self.name(enemy_id));
+				if let Some(enemy) = self.get_mut(enemy_id) {
+					enemy.scared += scary;
+					self.log.push(text, vec![id, enemy_id]);
+				}
+			}
+		}
+
+		// The creature may have fled while doing its action.
+		if let Some(creature) = self.get_mut(id) {
+			creature.cycle.actions.push(action);
+			creature.cycle.rotations = creature.cycle.rotations.wrapping_add(1);
+		}
+	}
+
+	/// Makes the given creature perform the given action (that is not necessarily
+	/// from its cycle), returns `true` if the creature actually did something.
+	fn perform(&mut self, id: CreatureId, action: &Action, is_scared: bool) -> bool {
+		match action {
+			Action::Null => false,
+			Action::Attack(_) if is_scared => {
+				// Too scared to attack, the attack is skipped.
+				self.log.push(format!("{} is too scared to attack", self.name(id)), vec![id]);
+				false
+			},
+			Action::Attack(damages) => {
+				if let Some(target) = self.front_enemy(id) {
+					self.deal_damages(id, target, *damages);
+				}
+				true
+			},
+			Action::Defend(block) => {
+				let text = format!("{} gains [block]{block} block[/]", self.name(id));
+				self.log.push(text, vec![id]);
+				if let Some(creature) = self.get_mut(id) {
+					creature.block += block;
+				}
+				true
+			},
+			Action::Heal(heal) => {
+				let target_id = self.most_wounded_ally(id);
+				let (name, target_name) = (self.name(id), self.name(target_id));
+				if let Some(target) = self.get_mut(target_id) {
+					let hp_before = target.hp;
+					target.hp = (target.hp + *heal as i32).min(target.max_hp);
+					let text = format!(
+						"{name} heals {target_name} for [heal]{heal}[/] ({target_name} {hp_before}→{})",
+						target.hp
+					);
+					let hp_gained = (target.hp - hp_before) as u32;
+					self.log.push(text, vec![id, target_id]);
+					self.events.push_back(BattleEvent::Healed { creature: target_id, hp_gained });
+				}
+				true
+			},
+			Action::Feed(food) => {
+				if let Some(target) = self.creature_in_front(id) {
+					self.give_food(target, *food, Some(id));
+				}
+				true
+			},
+			Action::Summon(card_spec) => {
+				let is_friend = self.is_friend(id);
+				let summoned_id = self.new_creature_id();
+				if let Some(summoned) = card_spec.instanciate_to_creature(summoned_id, &self.data) {
+					self.log.push(
+						format!("{} summons a {}", self.name(id), card_spec.name()),
+						vec![id, summoned_id],
+					);
+					if is_friend {
+						self.friends.push(summoned);
+					} else {
+						self.foes.push(summoned);
+					}
+				}
+				true
+			},
+			Action::Buff(status, stacks) => {
+				let text = format!("{} gets {stacks} {}", self.name(id), status.icon());
+				self.log.push(text, vec![id]);
+				if let Some(creature) = self.get_mut(id) {
+					*creature.status_mut(*status) += stacks;
+				}
+				true
+			},
+			Action::Soothe(anger) => {
+				let enemies: Vec<CreatureId> = if self.is_friend(id) {
+					self.foes.iter().map(|foe| foe.id).collect()
+				} else {
+					vec![]
+				};
+				self.log.push(
+					format!("{} soothes the foes ({anger} {{anger}} less)", self.name(id)),
+					vec![id],
+				);
+				for enemy in enemies {
+					self.lower_anger(enemy, *anger);
+				}
+				true
+			},
+			Action::Steal(status, stacks) => {
+				if let Some(target) = self.front_enemy(id) {
+					let target_stacks = self.get_mut(target).unwrap().status_mut(*status);
+					let stolen = (*stacks).min(*target_stacks);
+					*target_stacks -= stolen;
+					let text = format!(
+						"{} steals {stolen} {} from {}",
+						self.name(id),
+						status.icon(),
+						self.name(target)
+					);
+					self.log.push(text, vec![id, target]);
+					if let Some(creature) = self.get_mut(id) {
+						*creature.status_mut(*status) += stolen;
+					}
+				}
+				true
+			},
+			Action::Complex(script) => self.perform_steps(id, &script.steps, is_scared),
+		}
+	}
+
+	fn perform_steps(&mut self, id: CreatureId, steps: &[ScriptStep], is_scared: bool) -> bool {
+		let mut did_something = false;
+		for step in steps {
+			did_something |= match step {
+				ScriptStep::Do(action) => self.perform(id, action, is_scared),
+				ScriptStep::If { condition, then, otherwise } => {
+					if self.check(id, condition) {
+						self.perform_steps(id, then, is_scared)
+					} else {
+						self.perform_steps(id, otherwise, is_scared)
+					}
+				},
+			};
+		}
+		did_something
+	}
+
+	fn check(&self, id: CreatureId, condition: &Condition) -> bool {
+		let Some(creature) = self.get(id) else {
+			return false;
+		};
+		match condition {
+			Condition::Has(status, stacks) => creature.status(*status) >= *stacks,
+			Condition::IsAtFront => self.is_at_front(id),
+		}
+	}
+
+	/// Nobody dies, when a creature's hp reaches 0 it flees the battlefield.
+	/// Defeating a foe makes all the other foes angrier.
+	fn flee_if_defeated(&mut self, id: CreatureId) {
+		match self.position(id) {
+			Some(WhichBattlefieldCreature::Friend(WhichBattlefieldFriend(i)))
+				if self.friends[i].hp <= 0 =>
+			{
+				let creature = self.friends.remove(i);
+				self.log.push(format!("{} flees", creature.card_spec.name()), vec![]);
+				if !creature.card_spec.is_token() {
+					self.discard_pile.push(Card { card_spec: creature.card_spec });
+				}
+				self.events.push_back(BattleEvent::Fled { creature: id, was_friend: true });
+			},
+			Some(WhichBattlefieldCreature::Foe(WhichBattlefieldFoe(i))) if self.foes[i].hp <= 0 => {
+				let creature = self.foes.remove(i);
+				self.log.push(format!("{} flees", creature.card_spec.name()), vec![]);
+				for foe in self.foes.iter_mut() {
+					foe.anger += 1;
+				}
+				self.events.push_back(BattleEvent::Fled { creature: id, was_friend: false });
+			},
+			_ => {},
+		}
+	}
+
+	/// How many friends do not have enough food to eat at the end of the turn.
+	fn hungry_friend_count(&self) -> usize {
+		self.friends.iter().filter(|friend| friend.food < FRIEND_UPKEEP).count()
+	}
+
+	/// Is the turn that is about to be played a night turn?
+	fn is_night(&self) -> bool {
+		self.turn % (DAY_TURNS + NIGHT_TURNS) >= DAY_TURNS
+	}
+
+	/// Tells how long until the time of day changes, like "day (2 turns before dusk)".
+	fn day_night_clock(&self) -> String {
+		let turn_in_cycle = self.turn % (DAY_TURNS + NIGHT_TURNS);
+		let (time_of_day, turns_left, next) = if self.is_night() {
+			("night", DAY_TURNS + NIGHT_TURNS - turn_in_cycle, "dawn")
+		} else {
+			("day", DAY_TURNS - turn_in_cycle, "dusk")
+		};
+		let turns = if turns_left == 1 { "turn" } else { "turns" };
+		format!("{time_of_day} ({turns_left} {turns} before {next})")
+	}
+
+	/// Every creature acts, friends first (from the front to the back) and then foes,
+	/// and the other creatures react after each action.
+	/// During the night, everyone gets a night stack before acting.
+	/// At the end of the turn, friends eat (see `FRIEND_UPKEEP`).
+	///
+	/// Creatures that are summoned or befriended during the turn wait for the next turn
+	/// to act, and creatures that flee before acting do not act.
+	///
+	/// Returns everything that happened during the turn, in order, step by step.
+	fn play_turn(&mut self) -> Vec<TurnStep> {
+		let time_of_day = if self.is_night() { "night" } else { "day" };
+		self.log.push(format!("turn {} ({time_of_day})", self.turn + 1), vec![]);
+		self.events.push_back(BattleEvent::TurnStart);
+		let mut steps = vec![TurnStep { actor: None, effects: self.resolve_events() }];
+		if self.is_night() {
+			for creature in self.friends.iter_mut().chain(self.foes.iter_mut()) {
+				creature.night += 1;
+			}
+		}
+		self.turn_order = self.all_creatures().into();
+		while let Some(id) = self.turn_order.pop_front() {
+			self.creature_act(id);
+			steps.push(TurnStep { actor: Some(id), effects: self.resolve_events() });
+		}
+		for friend in self.friends.iter_mut() {
+			if friend.food >= FRIEND_UPKEEP {
+				friend.food -= FRIEND_UPKEEP;
+			} else {
+				friend.scared += 1;
+				let text = format!("{} is hungry and gets scared", friend.card_spec.name());
+				self.log.push(text, vec![friend.id]);
+			}
+		}
+		self.events.push_back(BattleEvent::TurnEnd);
+		steps.push(TurnStep { actor: None, effects: self.resolve_events() });
+		self.turn += 1;
+		steps
 	}
 }
 
@@ -156,52 +1330,115 @@ struct Card {
 
 enum InterfaceElementWhat {
 	HandCard(WhichHandCard),
-	Creature(WhichBattlefieldCreature),
+	Creature(CreatureId),
 	/// A targetable arrow that point to a space on the battlefield where a friend creature
 	/// could be placed. It contains the index that the creature will have in the friend vec
 	/// if placed here.
 	FriendInsertionSlot(usize),
 	/// A card floating around (for example when moving during an animation).
 	Card(Card),
-	Food,
+	/// Food flying around, spinning.
+	Food { rotation: f32 },
+	/// A button that makes every creature on the battlefield act.
+	EndTurnButton,
+	/// A button that takes back the last card played during the turn.
+	UndoButton,
+}
+
+impl InterfaceElementWhat {
+	/// What a card dropped on this element would be played on, if anything.
+	fn play_target(&self) -> Option<PlayTarget> {
+		match self {
+			InterfaceElementWhat::FriendInsertionSlot(index) => {
+				Some(PlayTarget::FriendInsertionSlot(*index))
+			},
+			InterfaceElementWhat::Creature(id) => Some(PlayTarget::Creature(*id)),
+			_ => None,
+		}
+	}
 }
 
 struct InterfaceElement {
+	/// Where the element is on the screen.
 	rect: Rect,
+	/// Elements of the battlefield can be zoomed out, the element is drawn at this scale.
+	scale: f32,
 	hovered: bool,
+	/// Has the keyboard/gamepad focus, which is displayed like being hovered.
+	focused: bool,
+	/// Involved in the hovered entry of the combat log, also displayed like being hovered.
+	highlighted: bool,
 	selected: bool,
 	targetable: bool,
 	what: InterfaceElementWhat,
 }
 
+/// How far along something that lasts some time is, according to the game clock
+/// (or its wall time, for feedback to the player).
 struct TimeProgression {
-	start: Instant,
+	clock: GameClock,
+	/// Timed with the wall time of the clock instead of its game time.
+	wall: bool,
+	start: Duration,
 	duration: Duration,
 }
 
 impl TimeProgression {
-	fn with_duration(duration: Duration) -> TimeProgression {
-		TimeProgression { start: Instant::now(), duration }
+	fn with_duration(clock: &GameClock, duration: Duration) -> TimeProgression {
+		TimeProgression { clock: clock.clone(), wall: false, start: clock.now(), duration }
+	}
+
+	/// For feedback to the player (like error messages), that must not be paused or sped up
+	/// with the animations, especially not finished before being seen at the instant speed.
+	fn with_wall_duration(clock: &GameClock, duration: Duration) -> TimeProgression {
+		TimeProgression { clock: clock.clone(), wall: true, start: clock.wall_now(), duration }
+	}
+
+	fn now(&self) -> Duration {
+		if self.wall {
+			self.clock.wall_now()
+		} else {
+			self.clock.now()
+		}
 	}
 
+	/// Goes from 0 to 1, and stays at 1 once it is over (so that animations do not
+	/// overshoot while waiting for `update` to notice that they are over).
 	fn progression(&self) -> f32 {
-		self.start.elapsed().as_secs_f32() / self.duration.as_secs_f32()
+		if self.duration.is_zero() {
+			return 1.0;
+		}
+		let elapsed = (self.now() - self.start).as_secs_f32();
+		(elapsed / self.duration.as_secs_f32()).min(1.0)
 	}
 }
 
+/// Shown for a moment when a card is dropped on something it cannot be played on,
+/// so that it does not look like the game ignored it.
+struct InvalidTargetFeedback {
+	tp: TimeProgression,
+	message: &'static str,
+	/// Where the card was dropped, outlined in red.
+	rect: Rect,
+}
+
 enum AnimationWhat {
 	PlacingCreatureFromHand {
 		src_hand_index: usize,
 		dst_friend_index: usize,
+		/// The creatures that move aside to make room for the placed one,
+		/// they stay the same even if others come and go during the animation.
+		making_room: Vec<CreatureId>,
 		card: Card,
 		src_point: Vec2,
 		dst_point: Vec2,
 	},
+	/// The food flies to the creature wherever it currently is,
+	/// it may move during the animation.
 	ApplyingFoodFromHand {
 		src_hand_index: usize,
-		dst_creature: WhichBattlefieldCreature,
+		dst_creature: CreatureId,
 		src_point: Vec2,
-		dst_point: Vec2,
 	},
 }
 
@@ -210,22 +1447,174 @@ struct Animation {
 	what: AnimationWhat,
 }
 
-/// The game! Its here ^^
-struct Game {
-	spritesheet: Image,
+/// How the battlefield line is displayed. It gets zoomed out when there are too many
+/// creatures to fit on the screen, and if that is not enough it can be scrolled horizontally
+/// (with the mouse wheel or by moving the cursor near the left or right edge).
+struct BattlefieldView {
+	/// Relative to the scale of the layout.
+	zoom: f32,
+	/// Horizontal offset of the line on the screen, in pixels.
+	scroll: f32,
+}
+
+impl BattlefieldView {
+	const MIN_ZOOM: f32 = 0.5;
+	/// Edge panning happens when the cursor is that close to the left or right edge.
+	const EDGE_PANNING_WIDTH: f32 = 40.0;
+	/// In pixels per second.
+	const EDGE_PANNING_SPEED: f32 = 800.0;
+	/// In pixels per mouse wheel step.
+	const WHEEL_SCROLL_SPEED: f32 = 60.0;
+}
+
+/// A run is a sequence of battles (in the same dream), with the deck growing
+/// with the friends made along the way.
+#[derive(Clone)]
+struct Run {
+	/// All the cards of the player, at the start of the next battle.
+	deck: Vec<Card>,
+	/// Starts at 1, later battles have more foes.
+	battle_number: u32,
+}
+
+impl Run {
+	fn new(data: &GameData) -> Run {
+		let deck = data.deck().into_iter().map(|card_spec| Card { card_spec }).collect();
+		Run { deck, battle_number: 1 }
+	}
+}
+
+/// Lists cards by how many of each there are, like "2 fwog, 1 dragon fly".
+fn card_counts<'a>(card_specs: impl Iterator<Item = &'a CardSpec>) -> String {
+	let mut counts: Vec<(&CardSpec, u32)> = vec![];
+	for card_spec in card_specs {
+		match counts.iter_mut().find(|(other, _count)| *other == card_spec) {
+			Some((_card_spec, count)) => *count += 1,
+			None => counts.push((card_spec, 1)),
+		}
+	}
+	let counts: Vec<String> =
+		counts.iter().map(|(card_spec, count)| format!("{count} {}", card_spec.name())).collect();
+	counts.join(", ")
+}
+
+/// The battlefield as it was before a card was played. The log is kept out of it,
+/// taking the card back only cuts the log back to the length it had.
+struct Snapshot {
+	battlefield: Battlefield,
+	log_len: usize,
+}
+
+impl Snapshot {
+	fn take(battlefield: &mut Battlefield) -> Snapshot {
+		let log = std::mem::take(&mut battlefield.log);
+		let log_len = log.entries().len();
+		let snapshot = Snapshot { battlefield: battlefield.clone(), log_len };
+		battlefield.log = log;
+		snapshot
+	}
+}
+
+/// The snapshots from before each card played during the turn, the last one is from before
+/// the last card played. It is emptied at the end of the turn.
+#[derive(Default)]
+struct UndoStack {
+	snapshots: Vec<Snapshot>,
+}
+
+impl UndoStack {
+	/// Plays the card like `Battlefield::play_card`, and keeps a snapshot to take it back.
+	fn play_card(
+		&mut self,
+		battlefield: &mut Battlefield,
+		hand_index: usize,
+		target: PlayTarget,
+	) -> Result<Effects, PlayError> {
+		let snapshot = Snapshot::take(battlefield);
+		let effects = battlefield.play_card(hand_index, target)?;
+		self.push(snapshot, battlefield);
+		Ok(effects)
+	}
+
+	/// Keeps the snapshot, unless chance was involved since it was taken (in which case
+	/// nothing played before can be taken back either).
+	fn push(&mut self, snapshot: Snapshot, battlefield: &Battlefield) {
+		if battlefield.random_outcome_count == snapshot.battlefield.random_outcome_count {
+			self.snapshots.push(snapshot);
+		} else {
+			self.snapshots.clear();
+		}
+	}
+
+	/// Ends the turn like `Battlefield::end_turn`. The turn cannot be taken back,
+	/// and neither can the cards played before.
+	fn end_turn(&mut self, battlefield: &mut Battlefield) -> Vec<TurnStep> {
+		self.snapshots.clear();
+		battlefield.end_turn()
+	}
+
+	/// Takes back the last card played during the turn, returns false if there is none.
+	fn undo(&mut self, battlefield: &mut Battlefield) -> bool {
+		let Some(snapshot) = self.snapshots.pop() else {
+			return false;
+		};
+		let mut log = std::mem::take(&mut battlefield.log);
+		log.truncate(snapshot.log_len);
+		log.push("(the last card played was taken back)".to_string(), vec![]);
+		*battlefield = snapshot.battlefield;
+		battlefield.log = log;
+		true
+	}
+
+	fn is_empty(&self) -> bool {
+		self.snapshots.is_empty()
+	}
+}
+
+/// The steps of a turn, shown one after another on the game clock so that players can
+/// follow who did what. The battlefield is already past them, nothing waits for them.
+struct TurnPlayback {
+	steps: VecDeque<TurnStep>,
+	/// Where the creatures were before the turn, for the ones that left since.
+	rects_before: Vec<(CreatureId, Rect)>,
+	/// Until the next step is shown.
+	tp: TimeProgression,
+}
+
+impl TurnPlayback {
+	const STEP_DURATION: Duration = Duration::from_millis(450);
+}
+
+/// A battle, the scene where the game is actually played ^^
+struct Battle {
+	run_battle_number: u32,
+	spritesheet: Spritesheet,
+	clock: GameClock,
+	audio: Audio,
 	canvas_size: (f32, f32),
+	layout: Layout,
 	battlefield: Battlefield,
-	hand: Vec<Card>,
+	battlefield_view: BattlefieldView,
 	selected_hand_card: Option<WhichHandCard>,
 	interface_elements: Vec<InterfaceElement>,
 	animation: Option<Animation>,
+	invalid_target_feedback: Option<InvalidTargetFeedback>,
+	undo_stack: UndoStack,
+	turn_playback: Option<TurnPlayback>,
+	visual_effects: VisualEffects,
+	cycle_views: CycleViews,
+	log_panel: LogPanel,
 	cursor_pos: Option<Vec2>,
+	/// Index (in the list of focusable elements) of the element that has the keyboard/gamepad
+	/// focus, if it is used.
+	focus: Option<usize>,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 struct WhichBattlefieldFriend(usize);
 #[derive(Clone, Copy, PartialEq, Eq)]
 struct WhichBattlefieldFoe(usize);
+/// Where a creature is on the battlefield, see `Battlefield::position`.
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum WhichBattlefieldCreature {
 	Friend(WhichBattlefieldFriend),
@@ -234,26 +1623,31 @@ enum WhichBattlefieldCreature {
 #[derive(Clone, Copy, PartialEq, Eq)]
 struct WhichHandCard(usize);
 
-impl Game {
-	fn new(ctx: &Context) -> GameResult<Game> {
-		let friends = vec![CardSpec::Fwog.instanciate_to_creature().unwrap()];
-		let foes = vec![CardSpec::DragonFly.instanciate_to_creature().unwrap()];
-		let hand = vec![
-			Card { card_spec: CardSpec::Fwog },
-			Card { card_spec: CardSpec::Fwog },
-			Card { card_spec: CardSpec::DragonFly },
-			Card { card_spec: CardSpec::Food },
-		];
-		Ok(Game {
-			spritesheet: Image::from_bytes(ctx, include_bytes!("../assets/spritesheet.png"))?,
+impl Battle {
+	fn new(ctx: &Context, shared: &Shared, run: Run) -> Battle {
+		// Friends start in the deck, they have to be played from the hand.
+		let foes = shared.data.encounter(run.battle_number);
+		Battle {
+			run_battle_number: run.battle_number,
+			spritesheet: shared.spritesheet.clone(),
+			clock: shared.clock.clone(),
+			audio: shared.audio.clone(),
 			canvas_size: ctx.gfx.size(),
-			battlefield: Battlefield { friends, foes },
-			hand,
+			layout: Layout::new(ctx.gfx.size(), ctx.gfx.window().scale_factor() as f32),
+			battlefield: Battlefield::new(vec![], foes, run.deck, &shared.data),
+			battlefield_view: BattlefieldView { zoom: 1.0, scroll: 0.0 },
 			selected_hand_card: None,
 			interface_elements: vec![],
 			animation: None,
+			invalid_target_feedback: None,
+			undo_stack: UndoStack::default(),
+			turn_playback: None,
+			visual_effects: VisualEffects::new(&shared.clock),
+			cycle_views: CycleViews::new(&shared.clock),
+			log_panel: LogPanel::new(&shared.spritesheet),
 			cursor_pos: None,
-		})
+			focus: None,
+		}
 	}
 
 	/// Returns what must be the rect of the given creature.
@@ -269,31 +1663,25 @@ impl Game {
 		// so that it can behaves like the number of cards is decremented but smoothly.
 		let (animation_offset, animation_len_offset) = if let Some(Animation {
 			tp,
-			what: AnimationWhat::PlacingCreatureFromHand { dst_friend_index, .. },
+			what: AnimationWhat::PlacingCreatureFromHand { making_room, .. },
 		}) = &self.animation
 		{
-			// A creature is being placed...
-			match which_creature {
-				WhichBattlefieldCreature::Friend(WhichBattlefieldFriend(i))
-					if *dst_friend_index > i =>
-				{
-					// ... and the one we are intrested in here has to make space.
-					(
-						(CardSpec::DIMS.0 + 10.0) * tp.progression(),
-						tp.progression(),
-					)
-				},
-				WhichBattlefieldCreature::Foe(_) => {
-					// ... and the one we are intrested in here has to make space.
-					(
-						(CardSpec::DIMS.0 + 10.0) * tp.progression(),
-						tp.progression(),
-					)
+			let progression = Easing::EaseOut.apply(tp.progression());
+			let id = match which_creature {
+				WhichBattlefieldCreature::Friend(WhichBattlefieldFriend(i)) => {
+					self.battlefield.friends.get(i).map(|friend| friend.id)
 				},
-				_ => {
-					// ... and the one we are intrested in here happens to not have to move.
-					(0.0, tp.progression())
+				WhichBattlefieldCreature::Foe(WhichBattlefieldFoe(i)) => {
+					self.battlefield.foes.get(i).map(|foe| foe.id)
 				},
+			};
+			// A creature is being placed...
+			if id.is_some_and(|id| making_room.contains(&id)) {
+				// ... and the one we are intrested in here has to make space.
+				((CardSpec::DIMS.0 + 10.0) * progression, progression)
+			} else {
+				// ... and the one we are intrested in here happens to not have to move.
+				(0.0, progression)
 			}
 		} else {
 			(0.0, 0.0)
@@ -309,8 +1697,7 @@ impl Game {
 		let x =
 			match which_creature {
 				WhichBattlefieldCreature::Friend(WhichBattlefieldFriend(i)) => {
-					self.canvas_size.0 / 2.0
-						- (CardSpec::DIMS.0 + 10.0) / 2.0
+					-(CardSpec::DIMS.0 + 10.0) / 2.0
 							* ((self.battlefield.friends.len() + self.battlefield.foes.len()) as f32
 								+ animation_len_offset + insertion_offset)
 						+ 10.0 / 2.0 - 40.0
@@ -320,8 +1707,7 @@ impl Game {
 				},
 
 				WhichBattlefieldCreature::Foe(WhichBattlefieldFoe(i)) => {
-					self.canvas_size.0 / 2.0
-						- (CardSpec::DIMS.0 + 10.0) / 2.0
+					-(CardSpec::DIMS.0 + 10.0) / 2.0
 							* ((self.battlefield.friends.len() + self.battlefield.foes.len()) as f32
 								+ animation_len_offset + insertion_offset)
 						+ 10.0 / 2.0 + 40.0
@@ -330,85 +1716,169 @@ impl Game {
 				},
 			};
 
-		Rect::new(x, 100.0, CardSpec::DIMS.0, CardSpec::DIMS.1)
+		self.battlefield_view_rect(Rect::new(x, 0.0, CardSpec::DIMS.0, CardSpec::DIMS.1))
+	}
+
+	/// The width of the battlefield line, at normal scale.
+	fn battlefield_line_width(&self) -> f32 {
+		let placing_creature = matches!(
+			self.animation,
+			Some(Animation { what: AnimationWhat::PlacingCreatureFromHand { .. }, .. })
+		);
+		let creature_count = self.battlefield.friends.len() +
+			self.battlefield.foes.len() +
+			if placing_creature { 1 } else { 0 };
+		(CardSpec::DIMS.0 + 10.0) * creature_count as f32 + 80.0
+	}
+
+	/// The scale at which the battlefield is displayed.
+	fn battlefield_scale(&self) -> f32 {
+		self.layout.scale * self.battlefield_view.zoom
+	}
+
+	/// How far the battlefield line can be scrolled in each direction from the center.
+	fn battlefield_max_scroll(&self) -> f32 {
+		let visible_width = self.layout.battlefield.w;
+		((self.battlefield_line_width() * self.battlefield_scale() - visible_width) / 2.0).max(0.0)
+	}
+
+	/// Zooms out just enough for the whole battlefield line to fit (if possible),
+	/// and keeps the scrolling in bounds.
+	fn refresh_battlefield_view(&mut self) {
+		let visible_width = self.layout.battlefield.w / self.layout.scale;
+		self.battlefield_view.zoom = (visible_width / self.battlefield_line_width())
+			.clamp(BattlefieldView::MIN_ZOOM, 1.0);
+		let max_scroll = self.battlefield_max_scroll();
+		self.battlefield_view.scroll = self.battlefield_view.scroll.clamp(-max_scroll, max_scroll);
+	}
+
+	fn scroll_battlefield(&mut self, scroll_delta: f32) {
+		let max_scroll = self.battlefield_max_scroll();
+		self.battlefield_view.scroll =
+			(self.battlefield_view.scroll + scroll_delta).clamp(-max_scroll, max_scroll);
+	}
+
+	/// Takes a rect of the battlefield (relative to the top center of the battlefield line,
+	/// at scale 1 and not scrolled) and returns where it is on the screen.
+	fn battlefield_view_rect(&self, rect: Rect) -> Rect {
+		let scale = self.battlefield_scale();
+		Rect::new(
+			self.layout.battlefield.center().x + rect.x * scale - self.battlefield_view.scroll,
+			self.layout.battlefield_cards_top() + rect.y * scale,
+			rect.w * scale,
+			rect.h * scale,
+		)
 	}
 
 	fn hand_card_rect(&self, which_hand_card: WhichHandCard) -> Rect {
 		let x = match which_hand_card {
 			WhichHandCard(i) => {
 				// If a card is being played, other hand cards might have fill the gap, smoothly.
-				// `animation_len_offset` is an offset to the length (in number of cards) of the hand,
-				// so that it can behaves like the number of cards is decremented but smoothly.
-				let (animation_offset, animation_len_offset) = if let Some(Animation {
+				// The played card stays in the hand until the animation is over, so the hand
+				// behaves like its length is decremented, but smoothly.
+				let (played_card_offset, animation_len_offset) = if let Some(Animation {
 					tp,
 					what:
 						AnimationWhat::PlacingCreatureFromHand { src_hand_index, .. }
 						| AnimationWhat::ApplyingFoodFromHand { src_hand_index, .. },
 				}) = &self.animation
 				{
-					// A creature is being placed...
-					if *src_hand_index <= i {
+					let progression = Easing::EaseOut.apply(tp.progression());
+					// A card is being played...
+					if *src_hand_index < i {
 						// ... and the one we are intrested in here has to move.
-						(
-							(CardSpec::DIMS.0 + 10.0) * (1.0 - tp.progression()),
-							1.0 - tp.progression(),
-						)
+						(progression, -progression)
 					} else {
 						// ... and the one we are intrested in here doesn't have to do much.
-						(0.0, 1.0 - tp.progression())
+						(0.0, -progression)
 					}
 				} else {
 					(0.0, 0.0)
 				};
 
-				self.canvas_size.0 / 2.0
-					- (CardSpec::DIMS.0 + 10.0) / 2.0 * (self.hand.len() as f32 + animation_len_offset)
-					+ (CardSpec::DIMS.0 + 10.0) * i as f32
-					+ animation_offset
+				-(CardSpec::DIMS.0 + 10.0) / 2.0
+					* (self.battlefield.hand.len() as f32 + animation_len_offset)
+					+ (CardSpec::DIMS.0 + 10.0) * (i as f32 - played_card_offset)
 			},
 		};
 
-		Rect::new(x, 500.0, CardSpec::DIMS.0, CardSpec::DIMS.1)
+		let scale = self.layout.scale;
+		Rect::new(
+			self.layout.hand.center().x + x * scale,
+			self.layout.hand.top() + 10.0 * scale,
+			CardSpec::DIMS.0 * scale,
+			CardSpec::DIMS.1 * scale,
+		)
 	}
 
 	fn refresh_interface(&mut self) {
 		self.interface_elements.clear();
+		// Creatures wait for their step of the turn to be shown to turn their cycle.
+		let waiting: Vec<CreatureId> = self
+			.turn_playback
+			.iter()
+			.flat_map(|playback| playback.steps.iter().filter_map(|step| step.actor))
+			.collect();
+		let creatures = self.battlefield.friends.iter().chain(self.battlefield.foes.iter());
+		self.cycle_views.sync(creatures, &waiting);
+		self.refresh_battlefield_view();
+		let zoom = self.battlefield_scale();
 
-		let creatures_are_targetable = if let Some(WhichHandCard(i)) = self.selected_hand_card {
-			let selected_card = &self.hand[i];
-			matches!(selected_card.card_spec, CardSpec::Food)
-		} else {
-			false
+		let is_valid_target = |target: PlayTarget| {
+			self.selected_hand_card.is_some_and(|WhichHandCard(i)| {
+				self.battlefield.check_play(i, target).is_ok()
+			})
 		};
+		// The creatures involved in the hovered entry of the combat log, if any.
+		let highlighted_creatures = self
+			.cursor_pos
+			.and_then(|pos| self.log_panel.hovered_entry(pos))
+			.map_or(&[][..], |i| &self.battlefield.log.entries()[i].creatures[..]);
 
-		for (i, _creature) in self.battlefield.friends.iter().enumerate() {
-			let which_creature = WhichBattlefieldCreature::Friend(WhichBattlefieldFriend(i));
-			let rect = self.creature_rect(which_creature, false);
+		for (i, creature) in self.battlefield.friends.iter().enumerate() {
+			let position = WhichBattlefieldCreature::Friend(WhichBattlefieldFriend(i));
+			let mut rect = self.creature_rect(position, false);
+			rect.translate(self.visual_effects.shake_offset(creature.id, zoom));
 			let hovered = self.cursor_pos.is_some_and(|pos| rect.contains(pos));
-			let what = InterfaceElementWhat::Creature(which_creature);
+			let what = InterfaceElementWhat::Creature(creature.id);
 			self.interface_elements.push(InterfaceElement {
 				rect,
+				scale: zoom,
 				hovered,
+				focused: false,
+				highlighted: highlighted_creatures.contains(&creature.id),
 				selected: false,
-				targetable: creatures_are_targetable,
+				targetable: is_valid_target(PlayTarget::Creature(creature.id)),
 				what,
 			});
 		}
-		for (i, _creature) in self.battlefield.foes.iter().enumerate() {
-			let which_creature = WhichBattlefieldCreature::Foe(WhichBattlefieldFoe(i));
-			let rect = self.creature_rect(which_creature, false);
+		for (i, creature) in self.battlefield.foes.iter().enumerate() {
+			let position = WhichBattlefieldCreature::Foe(WhichBattlefieldFoe(i));
+			let mut rect = self.creature_rect(position, false);
+			rect.translate(self.visual_effects.shake_offset(creature.id, zoom));
 			let hovered = self.cursor_pos.is_some_and(|pos| rect.contains(pos));
-			let what = InterfaceElementWhat::Creature(which_creature);
+			let what = InterfaceElementWhat::Creature(creature.id);
 			self.interface_elements.push(InterfaceElement {
 				rect,
+				scale: zoom,
 				hovered,
+				focused: false,
+				highlighted: highlighted_creatures.contains(&creature.id),
 				selected: false,
-				targetable: creatures_are_targetable,
+				targetable: is_valid_target(PlayTarget::Creature(creature.id)),
 				what,
 			});
 		}
 
-		for (i, _card) in self.hand.iter().enumerate() {
+		let played_hand_index = self.animation.as_ref().map(|animation| match animation.what {
+			AnimationWhat::PlacingCreatureFromHand { src_hand_index, .. } |
+			AnimationWhat::ApplyingFoodFromHand { src_hand_index, .. } => src_hand_index,
+		});
+		for (i, _card) in self.battlefield.hand.iter().enumerate() {
+			if played_hand_index == Some(i) {
+				// The card is drawn by its animation.
+				continue;
+			}
 			let which_hand_card = WhichHandCard(i);
 			let rect = self.hand_card_rect(which_hand_card);
 			let hovered = self.cursor_pos.is_some_and(|pos| rect.contains(pos));
@@ -416,89 +1886,218 @@ impl Game {
 			let what = InterfaceElementWhat::HandCard(which_hand_card);
 			self.interface_elements.push(InterfaceElement {
 				rect,
+				scale: self.layout.scale,
 				hovered,
+				focused: false,
+				highlighted: false,
 				selected,
 				targetable: false,
 				what,
 			});
 		}
 
-		let display_insert_slots = if let Some(WhichHandCard(i)) = self.selected_hand_card {
-			let selected_card = &self.hand[i];
-			selected_card.card_spec.is_creature()
-		} else {
-			false
-		};
+		let display_insert_slots = self.selected_hand_card.is_some_and(|WhichHandCard(i)| {
+			self.battlefield.hand.get(i).is_some_and(|card| card.card_spec.is_creature())
+		});
 		if display_insert_slots {
 			for i in 0..(self.battlefield.friends.len() + 1) {
-				let x = self
-					.creature_rect(
-						WhichBattlefieldCreature::Friend(WhichBattlefieldFriend(i)),
-						false,
-					)
-					.right() + 5.0;
-				let w = 50.0;
-				let rect = Rect::new(x - w / 2.0, 100.0 + CardSpec::DIMS.1 + 10.0, w, 50.0);
+				let creature_rect = self.creature_rect(
+					WhichBattlefieldCreature::Friend(WhichBattlefieldFriend(i)),
+					false,
+				);
+				let x = creature_rect.right() + 5.0 * zoom;
+				let w = 50.0 * zoom;
+				let y = creature_rect.bottom() + 10.0 * zoom;
+				let rect = Rect::new(x - w / 2.0, y, w, 50.0 * zoom);
 				let hovered = self.cursor_pos.is_some_and(|pos| rect.contains(pos));
 				let selected = false;
 				let what = InterfaceElementWhat::FriendInsertionSlot(i);
 				self.interface_elements.push(InterfaceElement {
 					rect,
+					scale: zoom,
 					hovered,
+					focused: false,
+					highlighted: false,
 					selected,
-					targetable: true,
+					targetable: is_valid_target(PlayTarget::FriendInsertionSlot(i)),
 					what,
 				});
 			}
 		}
 
+		let rect = self.layout.end_turn_button;
+		let hovered = self.cursor_pos.is_some_and(|pos| rect.contains(pos));
+		self.interface_elements.push(InterfaceElement {
+			rect,
+			scale: self.layout.scale,
+			hovered,
+			focused: false,
+			highlighted: false,
+			selected: false,
+			targetable: false,
+			what: InterfaceElementWhat::EndTurnButton,
+		});
+
+		let rect = self.layout.undo_button;
+		let hovered = self.cursor_pos.is_some_and(|pos| rect.contains(pos));
+		self.interface_elements.push(InterfaceElement {
+			rect,
+			scale: self.layout.scale,
+			hovered,
+			focused: false,
+			highlighted: false,
+			selected: false,
+			targetable: false,
+			what: InterfaceElementWhat::UndoButton,
+		});
+
 		if let Some(Animation { tp, what }) = &self.animation {
 			let progression = tp.progression();
 			match what {
 				AnimationWhat::PlacingCreatureFromHand { card, src_point, dst_point, .. } => {
-					let pos = Vec2::new(
-						lerp(progression, src_point.x, dst_point.x),
-						lerp(progression, src_point.y, dst_point.y),
-					);
+					// The card lands on its spot with a little bounce.
+					let pos = Tween::new(*src_point, *dst_point, Easing::Bounce).at(progression);
+					// The card gets smaller on its way if the battlefield is zoomed out.
+					let scale = Tween::new(self.layout.scale, zoom, Easing::Back).at(progression);
 					self.interface_elements.push(InterfaceElement {
-						rect: Rect::new(pos.x, pos.y, CardSpec::DIMS.0, CardSpec::DIMS.1),
+						rect: Rect::new(pos.x, pos.y, CardSpec::DIMS.0 * scale, CardSpec::DIMS.1 * scale),
+						scale,
 						hovered: false,
+						focused: false,
+						highlighted: false,
 						selected: false,
 						targetable: false,
 						what: InterfaceElementWhat::Card(card.clone()),
 					});
 				},
-				AnimationWhat::ApplyingFoodFromHand { src_point, dst_point, .. } => {
-					let pos = Vec2::new(
-						lerp(progression, src_point.x, dst_point.x),
-						lerp(progression, src_point.y, dst_point.y),
-					);
+				AnimationWhat::ApplyingFoodFromHand { dst_creature, src_point, .. } => {
+					let dst_point = self.food_destination(*dst_creature).unwrap_or(*src_point);
+					let pos = Tween::new(*src_point, dst_point, Easing::EaseOut).at(progression);
+					// The food pops out of the card and spins on its way.
+					let pop = Tween::new(0.3, 1.0, Easing::Back).at(progression * 2.0);
+					let rotation =
+						Tween::new(0.0, std::f32::consts::TAU, Easing::Linear).at(progression);
 					self.interface_elements.push(InterfaceElement {
 						rect: Rect::new(pos.x, pos.y, 0.0, 0.0),
+						scale: self.layout.scale * pop,
 						hovered: false,
+						focused: false,
+						highlighted: false,
 						selected: false,
 						targetable: false,
-						what: InterfaceElementWhat::Food,
+						what: InterfaceElementWhat::Food { rotation },
 					});
 				},
 			}
 		}
+
+		let focusable_elements = self.focusable_elements();
+		if let Some(focus) = self.focus {
+			if focusable_elements.is_empty() {
+				self.focus = None;
+			} else {
+				let focus = focus.min(focusable_elements.len() - 1);
+				self.focus = Some(focus);
+				self.interface_elements[focusable_elements[focus]].focused = true;
+			}
+		}
+	}
+
+	/// The elements that the keyboard/gamepad focus can go through: the hand cards and
+	/// the end turn button, or the valid targets if a card is selected.
+	/// Returns their indices in `interface_elements`.
+	fn focusable_elements(&self) -> Vec<usize> {
+		self.interface_elements
+			.iter()
+			.enumerate()
+			.filter(|(_i, elem)| {
+				if self.selected_hand_card.is_some() {
+					elem.targetable
+				} else {
+					match elem.what {
+						InterfaceElementWhat::HandCard(_) | InterfaceElementWhat::EndTurnButton => {
+							true
+						},
+						InterfaceElementWhat::UndoButton => !self.undo_stack.is_empty(),
+						_ => false,
+					}
+				}
+			})
+			.map(|(i, _elem)| i)
+			.collect()
+	}
+
+	/// Why the selected card cannot be played on the given element.
+	fn invalid_target_message(&self, element_index: usize) -> &'static str {
+		let Some(WhichHandCard(i)) = self.selected_hand_card else {
+			return "no card is selected";
+		};
+		match self.interface_elements[element_index].what.play_target() {
+			Some(target) => match self.battlefield.check_play(i, target) {
+				Err(error) => error.message(),
+				Ok(()) => "",
+			},
+			None if self.battlefield.hand[i].card_spec.is_creature() => {
+				"drop it on a slot below the friends"
+			},
+			None => "drop it on a creature",
+		}
+	}
+
+	/// Starts playing the selected hand card (if any) on the given element,
+	/// if it is a valid target. The card is actually played when its animation is over.
+	fn play_selected_card_on(&mut self, element_index: usize) {
+		let Some(WhichHandCard(src_hand_index)) = self.selected_hand_card else {
+			return;
+		};
+		let Some(target) = self.interface_elements[element_index].what.play_target() else {
+			return;
+		};
+		if self.battlefield.check_play(src_hand_index, target).is_err() {
+			return;
+		}
+		match target {
+			PlayTarget::FriendInsertionSlot(dst_friend_index) => {
+				self.place_creature_from_hand(src_hand_index, dst_friend_index);
+			},
+			PlayTarget::Creature(dst_creature) => {
+				self.apply_food_from_hand(src_hand_index, dst_creature);
+			},
+		}
 	}
 
 	fn draw_interface(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+		// Text to display near the cursor, drawn last so that it is on top of everything.
+		let mut tooltip: Option<(String, Vec2)> = None;
+		let (width, height) = self.canvas_size;
 		for elem in self.interface_elements.iter() {
+			// Each element is drawn as if it was at the origin and at its normal scale,
+			// and the projection places it where it actually is (scaled if it is zoomed out).
+			canvas.set_screen_coordinates(Rect::new(
+				-elem.rect.x / elem.scale,
+				-elem.rect.y / elem.scale,
+				width / elem.scale,
+				height / elem.scale,
+			));
+			let rect = Rect::new(0.0, 0.0, elem.rect.w / elem.scale, elem.rect.h / elem.scale);
+			let origin = Vec2::from(elem.rect.point());
+			let to_screen = |pos: Vec2| origin + pos * elem.scale;
+			let cursor_pos = self.cursor_pos.map(|pos| (pos - origin) / elem.scale);
 			match &elem.what {
 				InterfaceElementWhat::HandCard(WhichHandCard(i)) => {
-					let card = &self.hand[*i];
+					let card = &self.battlefield.hand[*i];
 					card.card_spec.draw(
 						ctx,
 						canvas,
 						&self.spritesheet,
-						elem.rect.point().into(),
+						&self.battlefield.data,
+						rect.point().into(),
 						CardDrawingParams {
-							hovered: elem.hovered,
+							hovered: elem.hovered || elem.focused || elem.highlighted,
 							selected: elem.selected,
 							targetable: elem.targetable,
+							pose: Pose::Idle,
+							animation_time: self.clock.now(),
 						},
 					)?;
 				},
@@ -507,95 +2106,138 @@ impl Game {
 						ctx,
 						canvas,
 						&self.spritesheet,
-						elem.rect.point().into(),
+						&self.battlefield.data,
+						rect.point().into(),
 						CardDrawingParams {
-							hovered: elem.hovered,
+							hovered: elem.hovered || elem.focused || elem.highlighted,
 							selected: elem.selected,
 							targetable: elem.targetable,
+							pose: Pose::Idle,
+							animation_time: self.clock.now(),
 						},
 					)?;
 				},
-				InterfaceElementWhat::Creature(which_creature) => {
-					let creature = match which_creature {
-						WhichBattlefieldCreature::Friend(WhichBattlefieldFriend(i)) => {
-							&self.battlefield.friends[*i]
-						},
-						WhichBattlefieldCreature::Foe(WhichBattlefieldFoe(i)) => {
-							&self.battlefield.foes[*i]
-						},
+				InterfaceElementWhat::Creature(id) => {
+					let Some(creature) = self.battlefield.get(*id) else {
+						continue;
 					};
 					creature.card_spec.draw(
 						ctx,
 						canvas,
 						&self.spritesheet,
-						elem.rect.point().into(),
+						&self.battlefield.data,
+						rect.point().into(),
 						CardDrawingParams {
-							hovered: elem.hovered,
+							hovered: elem.hovered || elem.focused || elem.highlighted,
 							selected: elem.selected,
 							targetable: elem.targetable,
+							pose: self.visual_effects.pose(*id),
+							animation_time: self.clock.now(),
 						},
 					)?;
-					let mut action_y = elem.rect.top() + 70.0;
-					for action in creature.cycle.actions.iter() {
-						let x = elem.rect.right() - 78.0;
+					for (action, placement) in self.cycle_views.boxes(*id) {
+						let action_y = rect.top() + 70.0 + 26.0 * placement.slot;
+						let x = rect.right() - 88.0 - 40.0 * placement.sideways;
+						let action_rect = Rect::new(x - 4.0, action_y - 2.0, 80.0, 20.0);
+						let rectangle = Mesh::new_rectangle(
+							ctx,
+							DrawMode::stroke(2.0),
+							action_rect,
+							Color { a: placement.alpha, ..Color::WHITE },
+						)?;
+						canvas.draw(&rectangle, Vec2::new(0.0, 0.0));
+						canvas.draw(
+							Text::new(action.label()).set_scale(18.0),
+							DrawParam::from(Vec2::new(x, action_y))
+								.color(Color { a: placement.alpha, ..action.color() }),
+						);
+						if cursor_pos.is_some_and(|pos| action_rect.contains(pos)) {
+							let pos = Vec2::new(action_rect.left(), action_rect.bottom());
+							tooltip = Some((action.description(), to_screen(pos)));
+						}
+					}
+					let mut reaction_y = rect.top() + 135.0;
+					for reaction in creature.reactions.iter() {
+						let x = rect.left() + 10.0;
+						let reaction_rect = Rect::new(x - 4.0, reaction_y - 2.0, 96.0, 34.0);
 						let rectangle = Mesh::new_rectangle(
 							ctx,
 							DrawMode::stroke(2.0),
-							Rect::new(x - 4.0, action_y - 2.0, 70.0, 20.0),
-							Color::WHITE,
+							reaction_rect,
+							Color::from_rgb(150, 150, 150),
 						)?;
 						canvas.draw(&rectangle, Vec2::new(0.0, 0.0));
-						match action {
-							Action::Null => {
-								canvas.draw(
-									Text::new("n").set_scale(18.0),
-									DrawParam::from(Vec2::new(x, action_y)).color(Color::WHITE),
-								);
-							},
-							Action::Attack(damages) => {
-								canvas.draw(
-									Text::new(format!("att {damages}")).set_scale(18.0),
-									DrawParam::from(Vec2::new(x, action_y))
-										.color(Color::from_rgb(255, 200, 150)),
-								);
-							},
+						canvas.draw(
+							Text::new(reaction.trigger.label()).set_scale(15.0),
+							DrawParam::from(Vec2::new(x, reaction_y))
+								.color(Color::from_rgb(180, 180, 180)),
+						);
+						canvas.draw(
+							Text::new(reaction.action.label()).set_scale(15.0),
+							DrawParam::from(Vec2::new(x, reaction_y + 15.0))
+								.color(reaction.action.color()),
+						);
+						if cursor_pos.is_some_and(|pos| reaction_rect.contains(pos)) {
+							let description = format!(
+								"{}, {}",
+								reaction.trigger.description(),
+								reaction.action.description()
+							);
+							let pos = Vec2::new(reaction_rect.left(), reaction_rect.bottom());
+							tooltip = Some((description, to_screen(pos)));
 						}
-						action_y += 26.0;
+						reaction_y += 38.0;
 					}
 					let hp = creature.hp;
 					canvas.draw(
 						Text::new(format!("{hp}")).set_scale(30.0),
-						DrawParam::from(Vec2::new(elem.rect.right() - 40.0, elem.rect.top() - 35.0))
+						DrawParam::from(Vec2::new(rect.right() - 40.0, rect.top() - 35.0))
 							.color(Color::from_rgb(255, 150, 180)),
 					);
-					if creature.food >= 1 {
-						let food = creature.food;
-						let sprite = Rect::new(0.0, 0.62, 0.5, 0.38);
+					if creature.block >= 1 {
+						let block = creature.block;
 						canvas.draw(
-							&self.spritesheet,
-							DrawParam::default()
-								.dest(Vec2::new(elem.rect.right() - 60.0, elem.rect.top() - 70.0))
-								.scale(Vec2::new(0.04, 0.04))
-								.src(sprite),
+							Text::new(format!("[{block}]")).set_scale(30.0),
+							DrawParam::from(Vec2::new(rect.right() - 95.0, rect.top() - 35.0))
+								.color(Color::from_rgb(170, 190, 220)),
 						);
+					}
+					if creature.food >= 1 {
+						let food = creature.food;
+						let badge_center = Vec2::new(rect.right() - 40.0, rect.top() - 55.0);
+						let sprite_name = CardSpec::Food.id();
+						self.spritesheet.draw(ctx, canvas, sprite_name, badge_center, 0.4, 0.0)?;
 						canvas.draw(
 							Text::new(format!("{food}")).set_scale(26.0),
-							DrawParam::from(Vec2::new(elem.rect.right() - 15.0, elem.rect.top() - 70.0))
-								.color(Color::from_rgb(255, 200, 140)),
+							DrawParam::from(Vec2::new(rect.right() - 15.0, rect.top() - 70.0))
+								.color(Status::Food.color()),
 						);
 					}
+					let mut status_x = rect.left() + 15.0;
+					for status in [Status::Anger, Status::Scary, Status::Scared, Status::Night] {
+						let stacks = creature.status(status);
+						if stacks >= 1 {
+							let center = Vec2::new(status_x, rect.top() - 20.0);
+							draw_status_icon(ctx, canvas, status, center, 1.0)?;
+							canvas.draw(
+								Text::new(format!("{stacks}")).set_scale(26.0),
+								DrawParam::from(center + Vec2::new(16.0, -13.0)).color(status.color()),
+							);
+							status_x += 50.0;
+						}
+					}
 				},
 				InterfaceElementWhat::FriendInsertionSlot(_index) => {
 					let triangle = Mesh::new_polyline(
 						ctx,
 						DrawMode::stroke(3.0),
 						&[
-							Vec2::new(elem.rect.center().x, elem.rect.top()),
-							Vec2::new(elem.rect.left(), elem.rect.bottom()),
-							Vec2::new(elem.rect.right(), elem.rect.bottom()),
-							Vec2::new(elem.rect.center().x, elem.rect.top()),
+							Vec2::new(rect.center().x, rect.top()),
+							Vec2::new(rect.left(), rect.bottom()),
+							Vec2::new(rect.right(), rect.bottom()),
+							Vec2::new(rect.center().x, rect.top()),
 						],
-						if elem.hovered {
+						if elem.hovered || elem.focused {
 							Color::from_rgb(180, 255, 0)
 						} else {
 							Color::CYAN
@@ -603,24 +2245,250 @@ impl Game {
 					)?;
 					canvas.draw(&triangle, Vec2::new(0.0, 0.0));
 				},
-				InterfaceElementWhat::Food => {
-					let sprite = Rect::new(0.0, 0.62, 0.5, 0.38);
+				InterfaceElementWhat::Food { rotation } => {
+					let sprite_name = CardSpec::Food.id();
+					let center = Vec2::new(rect.x, rect.y);
+					self.spritesheet.draw(ctx, canvas, sprite_name, center, 1.0, *rotation)?;
+				},
+				InterfaceElementWhat::EndTurnButton => {
+					let rectangle = Mesh::new_rectangle(
+						ctx,
+						DrawMode::stroke(3.0),
+						rect,
+						if elem.hovered || elem.focused { Color::YELLOW } else { Color::WHITE },
+					)?;
+					canvas.draw(&rectangle, Vec2::new(0.0, 0.0));
+					canvas.draw(
+						Text::new("end turn").set_scale(26.0),
+						DrawParam::from(Vec2::new(rect.left() + 22.0, rect.top() + 18.0))
+							.color(Color::WHITE),
+					);
+				},
+				InterfaceElementWhat::UndoButton => {
+					// Greyed out when there is nothing to undo.
+					let color = if self.undo_stack.is_empty() {
+						Color::from_rgb(120, 120, 120)
+					} else if elem.hovered || elem.focused {
+						Color::YELLOW
+					} else {
+						Color::WHITE
+					};
+					let rectangle = Mesh::new_rectangle(ctx, DrawMode::stroke(3.0), rect, color)?;
+					canvas.draw(&rectangle, Vec2::new(0.0, 0.0));
 					canvas.draw(
-						&self.spritesheet,
-						DrawParam::default()
-							.dest(ggez::mint::Point2::<f32>::from(Vec2::new(
-								elem.rect.x - sprite.w / 2.0,
-								elem.rect.y - sprite.h / 2.0,
-							)))
-							.scale(Vec2::new(0.1, 0.1))
-							.src(sprite),
+						Text::new("undo").set_scale(26.0),
+						DrawParam::from(Vec2::new(rect.left() + 28.0, rect.top() + 18.0)).color(color),
 					);
 				},
 			}
 		}
+		canvas.set_screen_coordinates(Rect::new(0.0, 0.0, width, height));
+
+		if let Some((text, pos)) = tooltip {
+			let scale = self.layout.scale;
+			let text = RichText::new(ctx, &text, 18.0 * scale, 260.0 * scale)?;
+			let dims = text.dims();
+			let background = Mesh::new_rectangle(
+				ctx,
+				DrawMode::fill(),
+				Rect::new(pos.x, pos.y + 4.0, dims.x + 12.0, dims.y + 8.0),
+				Color::from_rgb(30, 30, 50),
+			)?;
+			canvas.draw(&background, Vec2::new(0.0, 0.0));
+			text.draw(ctx, canvas, &self.spritesheet, pos + Vec2::new(6.0, 8.0), Color::WHITE)?;
+		}
+
 		Ok(())
 	}
 
+	fn end_turn(&mut self) {
+		// What is left of the previous turn is shown at once.
+		if let Some(playback) = self.turn_playback.take() {
+			for step in playback.steps {
+				self.show_visual_effects(&step.effects, &playback.rects_before);
+			}
+		}
+		let rects_before = self.creature_rects();
+		let steps = self.undo_stack.end_turn(&mut self.battlefield);
+		let steps = steps
+			.into_iter()
+			.filter(|step| step.actor.is_some() || !step.effects.is_empty())
+			.collect();
+		// The first step is shown at the next update.
+		let tp = TimeProgression::with_duration(&self.clock, Duration::ZERO);
+		self.turn_playback = Some(TurnPlayback { steps, rects_before, tp });
+	}
+
+	/// Shows the next step of the turn being played back, if it is time.
+	fn advance_turn_playback(&mut self) {
+		let Some(mut playback) = self.turn_playback.take() else {
+			return;
+		};
+		if playback.tp.progression() < 1.0 {
+			self.turn_playback = Some(playback);
+			return;
+		}
+		// Once the last step had its time, the playback is over.
+		if let Some(step) = playback.steps.pop_front() {
+			self.show_visual_effects(&step.effects, &playback.rects_before);
+			playback.tp = TimeProgression::with_duration(&self.clock, TurnPlayback::STEP_DURATION);
+			self.turn_playback = Some(playback);
+		}
+	}
+
+	/// Where each creature is displayed on the screen (as of the last interface refresh).
+	fn creature_rects(&self) -> Vec<(CreatureId, Rect)> {
+		self.interface_elements
+			.iter()
+			.filter_map(|elem| match elem.what {
+				InterfaceElementWhat::Creature(id) => Some((id, elem.rect)),
+				_ => None,
+			})
+			.collect()
+	}
+
+	/// Shows what happened on the battlefield (damages, heals, etc.) with visual effects.
+	/// Creatures that are not on the battlefield anymore are found in `rects_before`.
+	fn show_visual_effects(&mut self, effects: &[BattleEvent], rects_before: &[(CreatureId, Rect)]) {
+		let scale = self.layout.scale;
+		// Popups on the same creature are stacked so that they can all be read.
+		let mut popup_counts: Vec<CreatureId> = vec![];
+		for event in effects {
+			let (creature, text, color) = match *event {
+				BattleEvent::Damaged { creature, hp_lost: 0, blocked, .. } if blocked >= 1 => {
+					(creature, "blocked".to_string(), Color::from_rgb(150, 200, 255))
+				},
+				// Nothing to show for a hit that did nothing.
+				BattleEvent::Damaged { hp_lost: 0, .. } => continue,
+				BattleEvent::Damaged { creature, hp_lost, .. } => {
+					(creature, format!("-{hp_lost}"), Color::from_rgb(255, 80, 80))
+				},
+				BattleEvent::Healed { creature, hp_gained } if hp_gained >= 1 => {
+					(creature, format!("+{hp_gained}"), Color::from_rgb(120, 255, 120))
+				},
+				_ => continue,
+			};
+			let Some(rect) = self.creature_rect_by_id(creature, rects_before) else {
+				continue;
+			};
+			let count = popup_counts.iter().filter(|other| **other == creature).count();
+			popup_counts.push(creature);
+			let pos = Vec2::new(rect.center().x, rect.top() + (40.0 - 32.0 * count as f32) * scale);
+			self.visual_effects.popup(text, color, pos);
+		}
+		for event in effects {
+			match *event {
+				BattleEvent::Damaged { creature, attacker, .. } => {
+					self.visual_effects.hit(creature);
+					self.visual_effects.attack(attacker);
+					self.audio.play(SoundEffect::Hit);
+				},
+				BattleEvent::Fled { creature, .. } => {
+					self.audio.play(SoundEffect::Flee);
+					if let Some(rect) = self.creature_rect_by_id(creature, rects_before) {
+						self.visual_effects.poof(rect.center().into());
+					}
+				},
+				BattleEvent::Befriended { creature } => {
+					if let Some(rect) = self.creature_rect_by_id(creature, rects_before) {
+						self.visual_effects.hearts(rect.center().into());
+					}
+				},
+				_ => {},
+			}
+		}
+	}
+
+	/// Where the given creature is on the screen, or where it was in `rects_before`
+	/// if it is not on the battlefield anymore.
+	fn creature_rect_by_id(
+		&self,
+		id: CreatureId,
+		rects_before: &[(CreatureId, Rect)],
+	) -> Option<Rect> {
+		match self.battlefield.position(id) {
+			Some(position) => Some(self.creature_rect(position, false)),
+			None => rects_before.iter().find(|(other, _rect)| *other == id).map(|(_id, rect)| *rect),
+		}
+	}
+
+	/// Takes back the last card played during the turn, if any.
+	fn undo(&mut self) {
+		if self.animation.is_some() {
+			return;
+		}
+		if self.undo_stack.undo(&mut self.battlefield) {
+			self.log_panel.reset();
+			self.invalid_target_feedback = None;
+		}
+	}
+
+	/// The scene to show on top of the battle when it is over, if it is.
+	fn outcome(&self) -> Option<Box<dyn Scene>> {
+		if self.animation.is_some() || self.turn_playback.is_some() {
+			return None;
+		}
+		if self.battlefield.foes.is_empty() {
+			// Friends go back in the deck, with the foes that were befriended.
+			let friend_cards = self
+				.battlefield
+				.friends
+				.iter()
+				.filter(|friend| !friend.card_spec.is_token())
+				.map(|friend| Card { card_spec: friend.card_spec.clone() });
+			let deck = self.battlefield.deck().cloned().chain(friend_cards).collect();
+			let next_run = Run { deck, battle_number: self.run_battle_number + 1 };
+			Some(Box::new(VictoryScene::new(next_run)))
+		} else if self.battlefield.friends.is_empty()
+			&& !self.battlefield.deck().any(|card| card.card_spec.is_creature())
+		{
+			Some(Box::new(GameOverScene::new(self.run_battle_number - 1)))
+		} else {
+			None
+		}
+	}
+
+	/// The deck and roster indicator, for players to see how big their team is getting
+	/// and how much it costs.
+	fn draw_roster_hud(&self, canvas: &mut Canvas) {
+		let deck_size = self.battlefield.deck().count();
+		let creature_card_count =
+			self.battlefield.deck().filter(|card| card.card_spec.is_creature()).count();
+		let friend_count = self.battlefield.friends.len();
+		let upkeep = friend_count as u32 * FRIEND_UPKEEP;
+		let draw_pile_size = self.battlefield.draw_pile.len();
+		let discard_pile_size = self.battlefield.discard_pile.len();
+		let scale = self.layout.scale;
+		let x = self.layout.bottom_hud.left();
+		let y = self.layout.bottom_hud.top();
+		canvas.draw(
+			Text::new(format!(
+				"deck: {deck_size} cards, {creature_card_count} of which are creatures"
+			))
+			.set_scale(20.0 * scale),
+			DrawParam::from(Vec2::new(x, y)).color(Color::WHITE),
+		);
+		canvas.draw(
+			Text::new(format!("draw pile: {draw_pile_size}, discard pile: {discard_pile_size}"))
+				.set_scale(20.0 * scale),
+			DrawParam::from(Vec2::new(x, y + 25.0 * scale)).color(Color::WHITE),
+		);
+		canvas.draw(
+			Text::new(format!("{friend_count} friends eat {upkeep} food per turn"))
+				.set_scale(20.0 * scale),
+			DrawParam::from(Vec2::new(x, y + 50.0 * scale)).color(Status::Food.color()),
+		);
+		let hungry_friend_count = self.battlefield.hungry_friend_count();
+		if hungry_friend_count >= 1 {
+			canvas.draw(
+				Text::new(format!("{hungry_friend_count} will go hungry (+1 scared)"))
+					.set_scale(20.0 * scale),
+				DrawParam::from(Vec2::new(x, y + 75.0 * scale))
+					.color(Color::from_rgb(255, 120, 120)),
+			);
+		}
+	}
+
 	fn place_creature_from_hand(&mut self, src_hand_index: usize, dst_friend_index: usize) {
 		let src_point = self
 			.hand_card_rect(WhichHandCard(src_hand_index))
@@ -633,13 +2501,20 @@ impl Game {
 			)
 			.point()
 			.into();
-		let card = self.hand.remove(src_hand_index);
-		let duration = Duration::from_secs_f32(0.2);
+		// The friends in front of the slot and all the foes move aside.
+		let making_room = self.battlefield.friends[..dst_friend_index]
+			.iter()
+			.chain(self.battlefield.foes.iter())
+			.map(|creature| creature.id)
+			.collect();
+		let card = self.battlefield.hand[src_hand_index].clone();
+		let duration = Duration::from_secs_f32(0.35);
 		self.animation = Some(Animation {
-			tp: TimeProgression::with_duration(duration),
+			tp: TimeProgression::with_duration(&self.clock, duration),
 			what: AnimationWhat::PlacingCreatureFromHand {
 				src_hand_index,
 				dst_friend_index,
+				making_room,
 				card,
 				src_point,
 				dst_point,
@@ -647,167 +2522,519 @@ impl Game {
 		});
 	}
 
-	fn apply_food_from_hand(
-		&mut self,
-		src_hand_index: usize,
-		dst_creature: WhichBattlefieldCreature,
-	) {
+	fn apply_food_from_hand(&mut self, src_hand_index: usize, dst_creature: CreatureId) {
+		if !self.battlefield.contains(dst_creature) {
+			return;
+		}
 		let src_point = self
 			.hand_card_rect(WhichHandCard(src_hand_index))
 			.center()
 			.into();
-		let dst_point = self.creature_rect(dst_creature, false).center().into();
-		self.hand.remove(src_hand_index);
-		let duration = Duration::from_secs_f32(0.2);
+		let duration = Duration::from_secs_f32(0.35);
 		self.animation = Some(Animation {
-			tp: TimeProgression::with_duration(duration),
-			what: AnimationWhat::ApplyingFoodFromHand {
-				src_hand_index,
-				dst_creature,
-				src_point,
-				dst_point,
-			},
+			tp: TimeProgression::with_duration(&self.clock, duration),
+			what: AnimationWhat::ApplyingFoodFromHand { src_hand_index, dst_creature, src_point },
 		});
 	}
+
+	/// Where food given to the creature flies to right now, if it is still there.
+	fn food_destination(&self, dst_creature: CreatureId) -> Option<Vec2> {
+		let position = self.battlefield.position(dst_creature)?;
+		Some(self.creature_rect(position, false).center().into())
+	}
+}
+
+/// The icon of a status, about 32 pixels wide at a scale of 1.
+/// The food has no icon of its own here, it is a sprite of the spritesheet.
+fn draw_status_icon(
+	ctx: &mut Context,
+	canvas: &mut Canvas,
+	status: Status,
+	center: Vec2,
+	scale: f32,
+) -> GameResult {
+	match status {
+		Status::Scary => draw_scary_icon(ctx, canvas, center, scale),
+		Status::Scared => draw_scared_icon(ctx, canvas, center, scale),
+		Status::Night => draw_night_icon(ctx, canvas, center, scale),
+		Status::Anger => draw_anger_icon(ctx, canvas, center, scale),
+		Status::Food => Ok(()),
+	}
+}
+
+/// The scary status icon, a spiky purple star.
+fn draw_scary_icon(
+	ctx: &mut Context,
+	canvas: &mut Canvas,
+	center: Vec2,
+	scale: f32,
+) -> GameResult {
+	let param = DrawParam::default().dest(center).scale(Vec2::new(scale, scale));
+	let points: Vec<Vec2> = (0..10)
+		.map(|i| {
+			let angle = i as f32 * std::f32::consts::TAU / 10.0;
+			let radius = if i % 2 == 0 { 16.0 } else { 7.0 };
+			Vec2::new(angle.sin(), -angle.cos()) * radius
+		})
+		.collect();
+	let star = Mesh::new_polygon(ctx, DrawMode::fill(), &points, Color::from_rgb(180, 60, 230))?;
+	canvas.draw(&star, param);
+	Ok(())
+}
+
+/// The anger icon, a cartoon vein popping out.
+fn draw_anger_icon(
+	ctx: &mut Context,
+	canvas: &mut Canvas,
+	center: Vec2,
+	scale: f32,
+) -> GameResult {
+	let param = DrawParam::default().dest(center).scale(Vec2::new(scale, scale));
+	let color = Status::Anger.color();
+	for (dx, dy) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+		let corner = Mesh::new_line(
+			ctx,
+			&[
+				Vec2::new(dx * 4.0, dy * 14.0),
+				Vec2::new(dx * 4.0, dy * 4.0),
+				Vec2::new(dx * 14.0, dy * 4.0),
+			],
+			4.0,
+			color,
+		)?;
+		canvas.draw(&corner, param);
+	}
+	Ok(())
+}
+
+/// The night status icon, a moon crescent.
+fn draw_night_icon(
+	ctx: &mut Context,
+	canvas: &mut Canvas,
+	center: Vec2,
+	scale: f32,
+) -> GameResult {
+	let param = DrawParam::default().dest(center).scale(Vec2::new(scale, scale));
+	// The left half of a circle, minus the left half of a thinner ellipse.
+	let outer_arc = (0..=12).map(|i| {
+		let angle = std::f32::consts::FRAC_PI_2 + i as f32 * std::f32::consts::PI / 12.0;
+		Vec2::new(angle.cos(), angle.sin()) * 16.0
+	});
+	let inner_arc = (0..=12).rev().map(|i| {
+		let angle = std::f32::consts::FRAC_PI_2 + i as f32 * std::f32::consts::PI / 12.0;
+		Vec2::new(angle.cos() * 0.4, angle.sin()) * 16.0
+	});
+	let points: Vec<Vec2> = outer_arc.chain(inner_arc).collect();
+	let moon = Mesh::new_polygon(ctx, DrawMode::fill(), &points, Color::from_rgb(255, 230, 120))?;
+	canvas.draw(&moon, param);
+	Ok(())
 }
 
-fn lerp(progression: f32, start_value: f32, end_value: f32) -> f32 {
-	start_value + progression * (end_value - start_value)
+/// The scared status icon, a drop of cold sweat.
+fn draw_scared_icon(
+	ctx: &mut Context,
+	canvas: &mut Canvas,
+	center: Vec2,
+	scale: f32,
+) -> GameResult {
+	let param = DrawParam::default().dest(center).scale(Vec2::new(scale, scale));
+	let color = Color::from_rgb(120, 180, 255);
+	let drop_tip = Mesh::new_polygon(
+		ctx,
+		DrawMode::fill(),
+		&[Vec2::new(0.0, -16.0), Vec2::new(-9.0, 2.0), Vec2::new(9.0, 2.0)],
+		color,
+	)?;
+	canvas.draw(&drop_tip, param);
+	let drop_body = Mesh::new_circle(ctx, DrawMode::fill(), Vec2::new(0.0, 4.0), 10.0, 0.5, color)?;
+	canvas.draw(&drop_body, param);
+	Ok(())
 }
 
-impl ggez::event::EventHandler<ggez::GameError> for Game {
+impl Scene for Battle {
 	fn mouse_motion_event(
 		&mut self,
 		_ctx: &mut Context,
+		_shared: &mut Shared,
 		x: f32,
 		y: f32,
-		_dx: f32,
-		_dy: f32,
-	) -> GameResult {
+	) -> GameResult<Transition> {
 		self.cursor_pos = Some(Vec2::new(x, y));
+		// Using the mouse hides the keyboard/gamepad focus.
+		self.focus = None;
 		self.refresh_interface();
-		Ok(())
+		Ok(Transition::None)
 	}
 
 	fn mouse_button_down_event(
 		&mut self,
-		_ctx: &mut Context,
+		ctx: &mut Context,
+		_shared: &mut Shared,
 		button: ggez::event::MouseButton,
 		x: f32,
 		y: f32,
-	) -> GameResult {
+	) -> GameResult<Transition> {
+		if let ggez::event::MouseButton::Left = button {
+			let log = &self.battlefield.log;
+			let (rect, scale) = (self.layout.log_panel, self.layout.scale);
+			if self.log_panel.click(ctx, &self.clock, log, rect, scale, Vec2::new(x, y)) {
+				return Ok(Transition::None);
+			}
+		}
 		if self.animation.is_some() {
-			return Ok(());
+			return Ok(Transition::None);
 		}
 		if let ggez::event::MouseButton::Left = button {
 			for interface_element in self.interface_elements.iter() {
 				if interface_element.rect.contains(Vec2::new(x, y)) {
 					if let InterfaceElementWhat::HandCard(which_hand_card) = interface_element.what {
 						self.selected_hand_card = Some(which_hand_card);
+						self.audio.play(SoundEffect::CardPickup);
 						break;
 					}
 				}
 			}
 		}
 		self.refresh_interface();
-		Ok(())
+		Ok(Transition::None)
 	}
 
 	fn mouse_button_up_event(
 		&mut self,
 		_ctx: &mut Context,
+		_shared: &mut Shared,
 		button: ggez::event::MouseButton,
 		_x: f32,
 		_y: f32,
-	) -> GameResult {
+	) -> GameResult<Transition> {
 		if self.animation.is_some() {
-			return Ok(());
+			return Ok(Transition::None);
 		}
 		if let ggez::event::MouseButton::Left = button {
-			for interface_element in &self.interface_elements {
-				if interface_element.hovered && interface_element.targetable {
-					if let (
-						InterfaceElementWhat::FriendInsertionSlot(dst_friend_index),
-						Some(WhichHandCard(src_hand_index)),
-					) = (&interface_element.what, self.selected_hand_card)
-					{
-						// If we get here, it means that we previously selected a creature card in hand
-						// and dragged it over an insertion slot and released it.
-						// It shall translate in this card's creature being placed on the battlefield
-						// on the chosen spot.
-						self.place_creature_from_hand(src_hand_index, *dst_friend_index);
-					} else if let (
-						InterfaceElementWhat::Creature(dst_creature),
-						Some(WhichHandCard(src_hand_index)),
-					) = (&interface_element.what, self.selected_hand_card)
-					{
-						self.apply_food_from_hand(src_hand_index, *dst_creature);
-					}
-					break;
+			let end_turn_button_clicked = self.selected_hand_card.is_none() &&
+				self.interface_elements.iter().any(|interface_element| {
+					interface_element.hovered &&
+						matches!(interface_element.what, InterfaceElementWhat::EndTurnButton)
+				});
+			if end_turn_button_clicked {
+				self.end_turn();
+			}
+			let undo_button_clicked = self.selected_hand_card.is_none() &&
+				self.interface_elements.iter().any(|interface_element| {
+					interface_element.hovered &&
+						matches!(interface_element.what, InterfaceElementWhat::UndoButton)
+				});
+			if undo_button_clicked {
+				self.undo();
+			}
+			if let Some(which_selected_card) = self.selected_hand_card {
+				let released_on = self
+					.interface_elements
+					.iter()
+					.position(|interface_element| interface_element.hovered);
+				match released_on.map(|i| (i, &self.interface_elements[i])) {
+					// Clicking a card (without dragging it away) keeps it selected,
+					// so that it can then be played by clicking on a target.
+					Some((_i, interface_element))
+						if matches!(
+							interface_element.what,
+							InterfaceElementWhat::HandCard(which_hand_card)
+								if which_hand_card == which_selected_card
+						) => {},
+					Some((i, interface_element)) if interface_element.targetable => {
+						self.play_selected_card_on(i);
+						self.selected_hand_card = None;
+					},
+					Some((i, interface_element)) => {
+						self.invalid_target_feedback = Some(InvalidTargetFeedback {
+							tp: TimeProgression::with_wall_duration(
+								&self.clock,
+								Duration::from_secs_f32(1.5),
+							),
+							message: self.invalid_target_message(i),
+							rect: interface_element.rect,
+						});
+						self.selected_hand_card = None;
+					},
+					None => self.selected_hand_card = None,
 				}
 			}
+		} else if let ggez::event::MouseButton::Right = button {
 			self.selected_hand_card = None;
 		}
 		self.refresh_interface();
-		Ok(())
+		Ok(Transition::None)
+	}
+
+	fn mouse_wheel_event(
+		&mut self,
+		_ctx: &mut Context,
+		_shared: &mut Shared,
+		x: f32,
+		y: f32,
+	) -> GameResult<Transition> {
+		if self.cursor_pos.is_some_and(|pos| self.layout.log_panel.contains(pos)) {
+			self.log_panel.mouse_wheel(y);
+		} else {
+			self.scroll_battlefield((x - y) * BattlefieldView::WHEEL_SCROLL_SPEED);
+		}
+		self.refresh_interface();
+		Ok(Transition::None)
 	}
 
-	fn resize_event(&mut self, _ctx: &mut Context, width: f32, height: f32) -> GameResult {
+	fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
 		self.canvas_size = (width, height);
+		self.layout = Layout::new(self.canvas_size, ctx.gfx.window().scale_factor() as f32);
 		self.refresh_interface();
 		Ok(())
 	}
 
-	fn update(&mut self, _ctx: &mut Context) -> GameResult {
+	fn update(&mut self, ctx: &mut Context, shared: &mut Shared) -> GameResult<Transition> {
 		let an_animation_was_going_on = self.animation.is_some();
 
+		// Edge panning, when the cursor is near the left or right edge of the battlefield.
+		let mut panned = false;
+		if let Some(cursor_pos) = self.cursor_pos {
+			let battlefield = self.layout.battlefield;
+			let in_battlefield_band =
+				(battlefield.top()..battlefield.bottom()).contains(&cursor_pos.y);
+			let right_edge = self.layout.log_panel.left();
+			let direction = if cursor_pos.x < BattlefieldView::EDGE_PANNING_WIDTH {
+				-1.0
+			} else if (right_edge - BattlefieldView::EDGE_PANNING_WIDTH..right_edge)
+				.contains(&cursor_pos.x)
+			{
+				1.0
+			} else {
+				0.0
+			};
+			let can_pan = shared.settings.edge_panning && self.battlefield_max_scroll() > 0.0;
+			if in_battlefield_band && direction != 0.0 && can_pan {
+				let delta = ctx.time.delta().as_secs_f32();
+				self.scroll_battlefield(direction * BattlefieldView::EDGE_PANNING_SPEED * delta);
+				panned = true;
+			}
+		}
+
 		// When the animation is over, we remove it and apply its effects (if any) on the board.
 		if let Some(Animation { tp, .. }) = &self.animation {
 			if tp.progression() >= 1.0 {
-				match self.animation.take().unwrap().what {
-					AnimationWhat::PlacingCreatureFromHand { dst_friend_index, card, .. } => {
-						self.battlefield.friends.insert(
-							dst_friend_index,
-							card.card_spec.instanciate_to_creature().unwrap(),
-						);
+				let (src_hand_index, target, dst_rect) = match self.animation.take().unwrap().what {
+					AnimationWhat::PlacingCreatureFromHand {
+						src_hand_index,
+						dst_friend_index,
+						dst_point,
+						..
+					} => {
+						let zoom = self.battlefield_scale();
+						let dims = Vec2::new(CardSpec::DIMS.0, CardSpec::DIMS.1) * zoom;
+						(
+							src_hand_index,
+							PlayTarget::FriendInsertionSlot(dst_friend_index),
+							Rect::new(dst_point.x, dst_point.y, dims.x, dims.y),
+						)
 					},
-					AnimationWhat::ApplyingFoodFromHand { dst_creature, .. } => {
-						self.battlefield.get_mut(dst_creature).food += 2;
+					AnimationWhat::ApplyingFoodFromHand { src_hand_index, dst_creature, src_point } => {
+						let dst_point = self.food_destination(dst_creature).unwrap_or(src_point);
+						let size = 40.0 * self.layout.scale;
+						(
+							src_hand_index,
+							PlayTarget::Creature(dst_creature),
+							Rect::new(dst_point.x - size / 2.0, dst_point.y - size / 2.0, size, size),
+						)
+					},
+				};
+				// The board may have changed since the card was dropped,
+				// in which case the card just stays in the hand.
+				let rects_before = self.creature_rects();
+				match self.undo_stack.play_card(&mut self.battlefield, src_hand_index, target) {
+					Ok(effects) => {
+						self.audio.play(match target {
+							PlayTarget::FriendInsertionSlot(_) => SoundEffect::Placement,
+							PlayTarget::Creature(_) => SoundEffect::Food,
+						});
+						self.show_visual_effects(&effects, &rects_before);
+					},
+					Err(error) => {
+						self.invalid_target_feedback = Some(InvalidTargetFeedback {
+							tp: TimeProgression::with_wall_duration(
+								&self.clock,
+								Duration::from_secs_f32(1.5),
+							),
+							message: error.message(),
+							rect: dst_rect,
+						});
 					},
 				}
 			}
 		}
 
-		if an_animation_was_going_on {
+		// Visual effects do not block anything, they just go on until they are over.
+		let visual_effects_were_going_on = !self.visual_effects.is_empty();
+		self.visual_effects.remove_finished();
+		// Neither does the playback of the turn.
+		let turn_is_played_back = self.turn_playback.is_some();
+		self.advance_turn_playback();
+
+		let cycles_are_turning = self.cycle_views.is_animating();
+		let effects_are_going_on = visual_effects_were_going_on || turn_is_played_back;
+		if an_animation_was_going_on || panned || effects_are_going_on || cycles_are_turning {
 			self.refresh_interface();
 		}
 
-		Ok(())
+		let feedback_is_over = self
+			.invalid_target_feedback
+			.as_ref()
+			.is_some_and(|feedback| feedback.tp.progression() >= 1.0);
+		if feedback_is_over {
+			self.invalid_target_feedback = None;
+		}
+
+		Ok(match self.outcome() {
+			Some(outcome_scene) => Transition::Push(outcome_scene),
+			None => Transition::None,
+		})
 	}
 
-	fn draw(&mut self, ctx: &mut Context) -> GameResult {
-		let mut canvas = Canvas::from_frame(ctx, graphics::Color::from([0.1, 0.2, 0.3, 1.0]));
+	fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas, _shared: &Shared) -> GameResult {
+		self.draw_interface(ctx, canvas)?;
+		let creature_rects = self.creature_rects();
+		self.visual_effects.draw(ctx, canvas, self.layout.scale, |id| {
+			creature_rects.iter().find(|(other, _rect)| *other == id).map(|(_id, rect)| *rect)
+		})?;
+		self.draw_roster_hud(canvas);
+		self.log_panel.draw(
+			ctx,
+			canvas,
+			&self.battlefield.log,
+			self.layout.log_panel,
+			self.layout.scale,
+			self.cursor_pos,
+		)?;
 
-		self.draw_interface(ctx, &mut canvas)?;
+		// The day/night clock, so that players can see the night coming.
+		let clock_text = self.battlefield.day_night_clock();
+		let clock_pos = Vec2::new(self.layout.top_hud.left(), self.layout.top_hud.top() + 15.0);
+		canvas.draw(
+			Text::new(clock_text).set_scale(26.0 * self.layout.scale),
+			DrawParam::from(clock_pos).color(Color::WHITE),
+		);
 
 		// Draw a line from the selected card (if any) to the cursor to make it clear that
 		// we are going to do something with the selected card and whatever is going to be
 		// under the cursor when we release the mouse button.
 		if let Some(which_hand_card) = self.selected_hand_card {
 			let card_center = self.hand_card_rect(which_hand_card).center();
-			let cursor_pos = ctx.mouse.position();
+			// Or to the focused target, when playing with a keyboard or gamepad.
+			let focused_element = self.interface_elements.iter().find(|elem| elem.focused);
+			let line_end = match focused_element {
+				Some(elem) => elem.rect.center(),
+				None => ctx.mouse.position(),
+			};
 			let line = Mesh::new_line(
 				ctx,
-				&[card_center, cursor_pos],
-				12.0,
+				&[card_center, line_end],
+				12.0 * self.layout.scale,
 				Color::from_rgb(255, 150, 180),
 			)?;
 			canvas.draw(&line, Vec2::new(0.0, 0.0));
 		}
 
-		canvas.finish(ctx)?;
+		if let Some(InvalidTargetFeedback { tp, message, rect }) = &self.invalid_target_feedback {
+			let alpha = Tween::new(1.0, 0.0, Easing::Linear).at(tp.progression());
+			let color = Color::new(1.0, 0.3, 0.3, alpha);
+			let outline = Mesh::new_rectangle(ctx, DrawMode::stroke(6.0), *rect, color)?;
+			canvas.draw(&outline, Vec2::new(0.0, 0.0));
+			let pos = Vec2::new(rect.left(), rect.top() - 30.0 * self.layout.scale);
+			canvas.draw(
+				Text::new(*message).set_scale(24.0 * self.layout.scale),
+				DrawParam::from(pos).color(color),
+			);
+		}
+
 		Ok(())
 	}
+
+	fn music(&self) -> Option<Music> {
+		Some(Music::Battle)
+	}
+
+	fn background_color(&self) -> Color {
+		if self.battlefield.is_night() {
+			Color::from([0.03, 0.05, 0.12, 1.0])
+		} else {
+			Color::from([0.1, 0.2, 0.3, 1.0])
+		}
+	}
+
+	fn command_event(
+		&mut self,
+		_ctx: &mut Context,
+		_shared: &mut Shared,
+		command: Command,
+	) -> GameResult<Transition> {
+		if let Command::Pause = command {
+			// Escape cancels the selection first, if there is one.
+			if self.selected_hand_card.take().is_some() {
+				self.refresh_interface();
+				return Ok(Transition::None);
+			}
+			return Ok(Transition::Push(Box::new(PauseScene::new())));
+		}
+		if self.animation.is_some() {
+			return Ok(Transition::None);
+		}
+		let focusable_elements = self.focusable_elements();
+		match command {
+			Command::Previous | Command::Next if !focusable_elements.is_empty() => {
+				let count = focusable_elements.len();
+				self.focus = Some(match (self.focus, command) {
+					(None, _) => 0,
+					(Some(focus), Command::Previous) => (focus + count - 1) % count,
+					(Some(focus), _) => (focus + 1) % count,
+				});
+			},
+			Command::Confirm => {
+				if let Some(focus) = self.focus {
+					let element_index = focusable_elements[focus];
+					match self.interface_elements[element_index].what {
+						InterfaceElementWhat::HandCard(which_hand_card) => {
+							self.selected_hand_card = Some(which_hand_card);
+							self.audio.play(SoundEffect::CardPickup);
+							self.focus = Some(0);
+						},
+						InterfaceElementWhat::EndTurnButton => self.end_turn(),
+						InterfaceElementWhat::UndoButton => {
+							self.undo();
+							self.focus = Some(0);
+						},
+						_ => {
+							self.play_selected_card_on(element_index);
+							self.selected_hand_card = None;
+							self.focus = Some(0);
+						},
+					}
+				} else {
+					self.focus = Some(0);
+				}
+			},
+			Command::Cancel => {
+				self.selected_hand_card = None;
+				self.focus = Some(0);
+			},
+			Command::EndTurn => {
+				self.selected_hand_card = None;
+				self.end_turn();
+			},
+			Command::Undo => {
+				self.selected_hand_card = None;
+				self.undo();
+			},
+			_ => {},
+		}
+		self.refresh_interface();
+		Ok(Transition::None)
+	}
 }
 
 fn main() -> GameResult {
@@ -816,10 +3043,478 @@ fn main() -> GameResult {
 		.window_mode(
 			ggez::conf::WindowMode::default()
 				.resizable(true)
-				.dimensions(1200.0, 900.0),
+				.dimensions(1200.0, 900.0)
+				.min_dimensions(layout::MIN_WINDOW_SIZE.0, layout::MIN_WINDOW_SIZE.1),
 		)
 		.build()?;
-	let game = Game::new(&ctx)?;
+	let mut toasts = Toasts::default();
+	// Without an audio device the game goes on silently.
+	let audio = Audio::new().unwrap_or_else(|error| {
+		toasts.push(ToastKind::Error, format!("no sound: {error}"));
+		Audio::null()
+	});
+	let shared = Shared {
+		spritesheet: Spritesheet::load(&ctx)?,
+		data: GameData::load()?,
+		settings: Settings::load(&ctx),
+		clock: GameClock::new(),
+		toasts,
+		audio,
+	};
+	let hot_reload = HotReload::is_dev_mode().then(HotReload::new);
+	let scene_stack = SceneStack::new(Box::new(TitleScene::new(false)), shared, hot_reload);
 	// Lets gooooooo!! Frog Dream!!! Yaaay ^^
-	ggez::event::run(ctx, event_loop, game)
+	ggez::event::run(ctx, event_loop, scene_stack)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::clock::ClockSpeed;
+
+	#[test]
+	fn time_progression_is_clamped() {
+		let clock = GameClock::new();
+		let tp = TimeProgression::with_duration(&clock, Duration::from_secs(2));
+		assert_eq!(tp.progression(), 0.0);
+		clock.advance(Duration::from_secs(1));
+		assert_eq!(tp.progression(), 0.5);
+		clock.advance(Duration::from_secs(10));
+		assert_eq!(tp.progression(), 1.0);
+		assert_eq!(TimeProgression::with_duration(&clock, Duration::ZERO).progression(), 1.0);
+	}
+
+	#[test]
+	fn wall_time_progression_ignores_the_clock_speed() {
+		let clock = GameClock::new();
+		let tp = TimeProgression::with_wall_duration(&clock, Duration::from_secs(2));
+		clock.tick(Duration::from_secs(1), ClockSpeed::Instant);
+		assert_eq!(tp.progression(), 0.5);
+		clock.set_paused(true);
+		clock.tick(Duration::from_secs(1), ClockSpeed::Normal);
+		assert_eq!(tp.progression(), 1.0);
+	}
+
+	/// A fwog and a food in hand, against a dragon fly.
+	fn battlefield() -> Battlefield {
+		let data = GameData::load().unwrap();
+		let mut battlefield = Battlefield::new(vec![], vec![CardSpec::DragonFly], vec![], &data);
+		battlefield.hand =
+			vec![Card { card_spec: CardSpec::Fwog }, Card { card_spec: CardSpec::Food }];
+		battlefield
+	}
+
+	#[test]
+	fn invalid_plays_keep_the_card_in_hand() {
+		let foe = battlefield().foes[0].id;
+		for (hand_index, target, expected) in [
+			(2, PlayTarget::FriendInsertionSlot(0), PlayError::NoSuchCard),
+			(1, PlayTarget::FriendInsertionSlot(0), PlayError::NotACreatureCard),
+			(0, PlayTarget::Creature(foe), PlayError::NotPlayableOnCreatures),
+			(0, PlayTarget::FriendInsertionSlot(1), PlayError::TargetOutOfRange),
+			(1, PlayTarget::Creature(CreatureId(42)), PlayError::TargetGone),
+		] {
+			let mut battlefield = battlefield();
+			let next_creature_id = battlefield.next_creature_id;
+			let result = battlefield.play_card(hand_index, target);
+			assert!(matches!(result, Err(error) if error == expected), "{}", expected.message());
+			assert_eq!(battlefield.hand.len(), 2);
+			assert!(battlefield.friends.is_empty());
+			assert_eq!(battlefield.next_creature_id, next_creature_id);
+		}
+	}
+
+	#[test]
+	fn creature_cards_join_the_friends() {
+		let mut battlefield = battlefield();
+		assert!(battlefield.play_card(0, PlayTarget::FriendInsertionSlot(0)).is_ok());
+		assert_eq!(battlefield.hand.len(), 1);
+		assert!(battlefield.friends[0].card_spec == CardSpec::Fwog);
+		assert_eq!(battlefield.friends[0].hp, 4);
+	}
+
+	#[test]
+	fn food_cards_feed_and_go_to_the_discard_pile() {
+		let mut battlefield = battlefield();
+		let foe = battlefield.foes[0].id;
+		assert!(battlefield.play_card(1, PlayTarget::Creature(foe)).is_ok());
+		assert_eq!(battlefield.hand.len(), 1);
+		assert_eq!(battlefield.discard_pile.len(), 1);
+		assert_eq!(battlefield.foes[0].food, 2);
+	}
+
+	/// A battlefield without creatures nor cards, see `add_creature`.
+	fn empty_battlefield() -> Battlefield {
+		Battlefield::new(vec![], vec![], vec![], &GameData::load().unwrap())
+	}
+
+	/// Adds a creature with 10 hp, that only does the given actions and has no reactions,
+	/// at the back of its side.
+	fn add_creature(
+		battlefield: &mut Battlefield,
+		is_friend: bool,
+		actions: Vec<Action>,
+	) -> CreatureId {
+		let id = battlefield.new_creature_id();
+		let creature = Creature::new(CardSpec::Fwog, id, 10, actions);
+		if is_friend {
+			battlefield.friends.push(creature);
+		} else {
+			battlefield.foes.push(creature);
+		}
+		id
+	}
+
+	#[test]
+	fn scared_creatures_skip_attacks_and_lose_a_stack() {
+		let mut battlefield = empty_battlefield();
+		let friend = add_creature(&mut battlefield, true, vec![Action::Attack(3), Action::Null]);
+		let foe = add_creature(&mut battlefield, false, vec![Action::Null]);
+		battlefield.get_mut(friend).unwrap().scared = 2;
+		battlefield.creature_act(friend);
+		assert_eq!(battlefield.get(foe).unwrap().hp, 10);
+		let friend = battlefield.get(friend).unwrap();
+		assert_eq!(friend.scared, 1);
+		// The skipped attack still goes to the bottom of the cycle.
+		assert!(friend.cycle.actions == [Action::Null, Action::Attack(3)]);
+	}
+
+	#[test]
+	fn scary_creatures_only_scare_adjacent_enemies_when_doing_something() {
+		let mut battlefield = empty_battlefield();
+		let front = add_creature(&mut battlefield, true, vec![Action::Null, Action::Defend(1)]);
+		let back = add_creature(&mut battlefield, true, vec![Action::Defend(1)]);
+		let front_foe = add_creature(&mut battlefield, false, vec![]);
+		let back_foe = add_creature(&mut battlefield, false, vec![]);
+		for id in [front, back] {
+			battlefield.get_mut(id).unwrap().scary = 2;
+		}
+		let scared = |battlefield: &Battlefield| {
+			[front_foe, back_foe].map(|id| battlefield.get(id).unwrap().scared)
+		};
+		battlefield.creature_act(front);
+		assert_eq!(scared(&battlefield), [0, 0]);
+		battlefield.creature_act(back);
+		assert_eq!(scared(&battlefield), [0, 0]);
+		battlefield.creature_act(front);
+		assert_eq!(scared(&battlefield), [2, 0]);
+	}
+
+	#[test]
+	fn night_stacks_turn_actions_into_null_actions() {
+		let mut battlefield = empty_battlefield();
+		let friend = add_creature(&mut battlefield, true, vec![Action::Null, Action::Attack(3)]);
+		let foe = add_creature(&mut battlefield, false, vec![]);
+		battlefield.get_mut(friend).unwrap().night = 1;
+		// Null actions do not use up night stacks.
+		battlefield.creature_act(friend);
+		assert_eq!(battlefield.get(friend).unwrap().night, 1);
+		battlefield.creature_act(friend);
+		assert_eq!(battlefield.get(friend).unwrap().night, 0);
+		assert_eq!(battlefield.get(foe).unwrap().hp, 10);
+		battlefield.creature_act(friend);
+		battlefield.creature_act(friend);
+		assert_eq!(battlefield.get(foe).unwrap().hp, 7);
+	}
+
+	#[test]
+	fn everyone_gets_a_night_stack_on_night_turns() {
+		let mut battlefield = empty_battlefield();
+		let friend = add_creature(&mut battlefield, true, vec![Action::Null]);
+		let foe = add_creature(&mut battlefield, false, vec![Action::Null]);
+		let night =
+			|battlefield: &Battlefield| [friend, foe].map(|id| battlefield.get(id).unwrap().night);
+		for _ in 0..DAY_TURNS {
+			battlefield.play_turn();
+		}
+		assert_eq!(night(&battlefield), [0, 0]);
+		battlefield.play_turn();
+		assert_eq!(night(&battlefield), [1, 1]);
+	}
+
+	#[test]
+	fn the_day_night_clock_counts_down_to_dawn_and_dusk() {
+		let mut battlefield = empty_battlefield();
+		let mut clocks = vec![];
+		for _ in 0..=DAY_TURNS + NIGHT_TURNS {
+			clocks.push(battlefield.day_night_clock());
+			battlefield.play_turn();
+		}
+		assert_eq!(
+			clocks,
+			[
+				"day (4 turns before dusk)",
+				"day (3 turns before dusk)",
+				"day (2 turns before dusk)",
+				"day (1 turn before dusk)",
+				"night (2 turns before dawn)",
+				"night (1 turn before dawn)",
+				"day (4 turns before dusk)",
+			]
+		);
+	}
+
+	#[test]
+	fn block_absorbs_damages_before_hp() {
+		let mut battlefield = empty_battlefield();
+		let friend = add_creature(&mut battlefield, true, vec![Action::Defend(2)]);
+		let foe = add_creature(&mut battlefield, false, vec![Action::Attack(3)]);
+		battlefield.creature_act(friend);
+		assert_eq!(battlefield.get(friend).unwrap().block, 2);
+		battlefield.creature_act(foe);
+		let friend = battlefield.get(friend).unwrap();
+		assert_eq!((friend.hp, friend.block), (9, 0));
+	}
+
+	#[test]
+	fn heals_go_to_the_most_wounded_ally_up_to_its_max_hp() {
+		let mut battlefield = empty_battlefield();
+		let healer = add_creature(&mut battlefield, true, vec![Action::Heal(5)]);
+		let slightly_wounded = add_creature(&mut battlefield, true, vec![]);
+		let wounded = add_creature(&mut battlefield, true, vec![]);
+		battlefield.get_mut(slightly_wounded).unwrap().hp = 9;
+		battlefield.get_mut(wounded).unwrap().hp = 7;
+		battlefield.creature_act(healer);
+		assert_eq!(battlefield.get(wounded).unwrap().hp, 10);
+		assert_eq!(battlefield.get(slightly_wounded).unwrap().hp, 9);
+	}
+
+	#[test]
+	fn food_goes_to_the_creature_in_front() {
+		let mut battlefield = empty_battlefield();
+		let front = add_creature(&mut battlefield, true, vec![Action::Feed(1)]);
+		let back = add_creature(&mut battlefield, true, vec![Action::Feed(2)]);
+		let foe = add_creature(&mut battlefield, false, vec![]);
+		battlefield.creature_act(back);
+		assert_eq!(battlefield.get(front).unwrap().food, 2);
+		assert_eq!(battlefield.get(back).unwrap().food, 0);
+		battlefield.creature_act(front);
+		assert_eq!(battlefield.get(foe).unwrap().food, 1);
+	}
+
+	#[test]
+	fn summoned_creatures_come_at_the_back_of_their_side() {
+		let mut battlefield = empty_battlefield();
+		let summoner = add_creature(&mut battlefield, true, vec![Action::Summon(CardSpec::Tadpole)]);
+		let other = add_creature(&mut battlefield, true, vec![]);
+		let foe = add_creature(&mut battlefield, false, vec![Action::Summon(CardSpec::Tadpole)]);
+		add_creature(&mut battlefield, false, vec![]);
+		battlefield.creature_act(summoner);
+		battlefield.creature_act(foe);
+		let ids = |side: &[Creature]| side.iter().map(|creature| creature.id).collect::<Vec<_>>();
+		assert_eq!(ids(&battlefield.friends)[..2], [summoner, other]);
+		assert!(battlefield.friends[2].card_spec == CardSpec::Tadpole);
+		assert_eq!(battlefield.foes.len(), 3);
+		assert!(battlefield.foes[2].card_spec == CardSpec::Tadpole);
+	}
+
+	#[test]
+	fn buffs_give_stacks_to_the_creature_itself() {
+		let mut battlefield = empty_battlefield();
+		let friend = add_creature(&mut battlefield, true, vec![Action::Buff(Status::Scary, 2)]);
+		let foe = add_creature(&mut battlefield, false, vec![]);
+		battlefield.creature_act(friend);
+		assert_eq!(battlefield.get(friend).unwrap().scary, 2);
+		assert_eq!(battlefield.get(foe).unwrap().scary, 0);
+	}
+
+	#[test]
+	fn script_steps_are_done_in_order() {
+		let mut battlefield = empty_battlefield();
+		let steps = vec![
+			ScriptStep::Do(Action::Buff(Status::Food, 1)),
+			ScriptStep::Do(Action::Feed(1)),
+		];
+		let friend = add_creature(&mut battlefield, true, vec![Action::Complex(Script { steps })]);
+		let foe = add_creature(&mut battlefield, false, vec![]);
+		battlefield.creature_act(friend);
+		assert_eq!(battlefield.get(friend).unwrap().food, 1);
+		assert_eq!(battlefield.get(foe).unwrap().food, 1);
+	}
+
+	#[test]
+	fn script_conditions_choose_the_steps() {
+		let mut battlefield = empty_battlefield();
+		let script = Script {
+			steps: vec![ScriptStep::If {
+				condition: Condition::Has(Status::Food, 1),
+				then: vec![ScriptStep::Do(Action::Attack(3))],
+				otherwise: vec![ScriptStep::Do(Action::Buff(Status::Food, 1))],
+			}],
+		};
+		let friend = add_creature(&mut battlefield, true, vec![Action::Complex(script)]);
+		let foe = add_creature(&mut battlefield, false, vec![]);
+		battlefield.creature_act(friend);
+		assert_eq!(battlefield.get(friend).unwrap().food, 1);
+		assert_eq!(battlefield.get(foe).unwrap().hp, 10);
+		battlefield.creature_act(friend);
+		assert_eq!(battlefield.get(foe).unwrap().hp, 7);
+
+		let front = battlefield.friends[0].id;
+		let back = add_creature(&mut battlefield, true, vec![]);
+		assert!(battlefield.check(front, &Condition::IsAtFront));
+		assert!(!battlefield.check(back, &Condition::IsAtFront));
+		assert!(battlefield.check(foe, &Condition::IsAtFront));
+	}
+
+	#[test]
+	fn long_script_summaries_are_cut() {
+		let short = Script { steps: vec![ScriptStep::Do(Action::Defend(1))] };
+		assert_eq!(short.summary(), "def 1");
+		let long = Script {
+			steps: vec![ScriptStep::If {
+				condition: Condition::Has(Status::Food, 1),
+				then: vec![ScriptStep::Do(Action::Attack(3))],
+				otherwise: vec![ScriptStep::Do(Action::Steal(Status::Food, 1))],
+			}],
+		};
+		assert_eq!(long.summary(), "food?att…");
+		assert_eq!(long.summary().chars().count(), Script::SUMMARY_MAX_LEN);
+	}
+
+	#[test]
+	fn reactions_are_done_by_event_and_then_by_position() {
+		let mut battlefield = empty_battlefield();
+		let [front, back] = [(); 2].map(|_| add_creature(&mut battlefield, true, vec![]));
+		let [front_foe, back_foe] = [(); 2].map(|_| add_creature(&mut battlefield, false, vec![]));
+		let reaction = |trigger, action| Reaction { trigger, action };
+		let defend = |trigger| reaction(trigger, Action::Defend(1));
+		battlefield.get_mut(front).unwrap().reactions =
+			vec![reaction(Trigger::WhenCardPlayed, Action::Attack(1))];
+		battlefield.get_mut(back).unwrap().reactions =
+			vec![defend(Trigger::AtTurnEnd), defend(Trigger::WhenCardPlayed)];
+		battlefield.get_mut(front_foe).unwrap().reactions =
+			vec![defend(Trigger::WhenHit), defend(Trigger::WhenCardPlayed)];
+		battlefield.get_mut(back_foe).unwrap().reactions = vec![defend(Trigger::AtTurnEnd)];
+		let log_start = battlefield.log.entries().len();
+		battlefield.events.extend([BattleEvent::CardPlayed, BattleEvent::TurnEnd]);
+		battlefield.resolve_events();
+		let creatures: Vec<Vec<CreatureId>> = battlefield.log.entries()[log_start..]
+			.iter()
+			.map(|entry| entry.creatures.clone())
+			.collect();
+		// The hit from the first reaction happens after the end of the turn,
+		// so the foe reacts to it last.
+		assert_eq!(
+			creatures,
+			[
+				vec![front, front_foe],
+				vec![back],
+				vec![front_foe],
+				vec![back],
+				vec![back_foe],
+				vec![front_foe],
+			]
+		);
+	}
+
+	#[test]
+	fn reaction_loops_stop() {
+		let mut battlefield = empty_battlefield();
+		let friend = add_creature(&mut battlefield, true, vec![]);
+		let foe = add_creature(&mut battlefield, false, vec![]);
+		// They feed each other whenever they get food.
+		for id in [friend, foe] {
+			battlefield.get_mut(id).unwrap().reactions =
+				vec![Reaction { trigger: Trigger::WhenFed, action: Action::Feed(1) }];
+		}
+		battlefield.give_food(friend, 1, None);
+		battlefield.resolve_events();
+		let food: u32 = [friend, foe].map(|id| battlefield.get(id).unwrap().food).iter().sum();
+		assert_eq!(food as usize, 1 + MAX_REACTIONS_PER_RESOLUTION);
+		assert!(battlefield.events.is_empty() && battlefield.reactions_to_do.is_empty());
+		let last_entry = battlefield.log.entries().last().unwrap();
+		assert!(last_entry.text.starts_with("the dream settles"));
+	}
+
+	#[test]
+	fn friends_without_enough_food_get_hungry() {
+		let mut battlefield = empty_battlefield();
+		let fed = add_creature(&mut battlefield, true, vec![Action::Null]);
+		let hungry = add_creature(&mut battlefield, true, vec![Action::Null]);
+		let foe = add_creature(&mut battlefield, false, vec![Action::Null]);
+		battlefield.get_mut(fed).unwrap().food = FRIEND_UPKEEP + 1;
+		assert_eq!(battlefield.hungry_friend_count(), 1);
+		battlefield.play_turn();
+		let (fed, hungry) = (battlefield.get(fed).unwrap(), battlefield.get(hungry).unwrap());
+		assert_eq!((fed.food, fed.scared), (1, 0));
+		assert_eq!((hungry.food, hungry.scared), (0, 1));
+		// Foes do not eat.
+		assert_eq!(battlefield.get(foe).unwrap().scared, 0);
+	}
+
+	#[test]
+	fn the_discard_pile_is_shuffled_back_when_the_draw_pile_is_empty() {
+		let deck = vec![Card { card_spec: CardSpec::Food }; Battlefield::HAND_SIZE + 2];
+		let mut battlefield = Battlefield::new(vec![], vec![], deck, &GameData::load().unwrap());
+		assert_eq!(battlefield.hand.len(), Battlefield::HAND_SIZE);
+		assert_eq!(battlefield.draw_pile.len(), 2);
+		battlefield.end_turn();
+		// The 2 cards left were drawn, and then the discarded hand was shuffled to draw the rest.
+		assert_eq!(battlefield.hand.len(), Battlefield::HAND_SIZE);
+		assert_eq!(battlefield.draw_pile.len(), 2);
+		assert!(battlefield.discard_pile.is_empty());
+		assert_eq!(battlefield.random_outcome_count, 1);
+	}
+
+	#[test]
+	fn each_card_played_can_be_taken_back() {
+		let mut battlefield = battlefield();
+		let mut undo_stack = UndoStack::default();
+		let log_len = battlefield.log.entries().len();
+		undo_stack.play_card(&mut battlefield, 0, PlayTarget::FriendInsertionSlot(0)).unwrap();
+		let fwog = battlefield.friends[0].id;
+		undo_stack.play_card(&mut battlefield, 0, PlayTarget::Creature(fwog)).unwrap();
+		assert!(undo_stack.play_card(&mut battlefield, 5, PlayTarget::Creature(fwog)).is_err());
+		assert_eq!(undo_stack.snapshots.len(), 2);
+		assert!(undo_stack.undo(&mut battlefield));
+		assert_eq!((battlefield.hand.len(), battlefield.friends[0].food), (1, 0));
+		assert!(undo_stack.undo(&mut battlefield));
+		assert_eq!((battlefield.hand.len(), battlefield.friends.len()), (2, 0));
+		assert!(!undo_stack.undo(&mut battlefield));
+		// What was logged by the plays is gone, and each undo is logged instead.
+		let entries = battlefield.log.entries();
+		assert_eq!(entries.len(), log_len + 1);
+		assert!(entries.iter().skip(log_len).all(|entry| entry.text.contains("taken back")));
+	}
+
+	#[test]
+	fn cards_played_cannot_be_taken_back_after_the_turn() {
+		let mut battlefield = battlefield();
+		let mut undo_stack = UndoStack::default();
+		undo_stack.play_card(&mut battlefield, 0, PlayTarget::FriendInsertionSlot(0)).unwrap();
+		assert!(!undo_stack.is_empty());
+		undo_stack.end_turn(&mut battlefield);
+		assert!(undo_stack.is_empty());
+		assert!(!undo_stack.undo(&mut battlefield));
+	}
+
+	#[test]
+	fn cards_played_cannot_be_taken_back_after_chance_was_involved() {
+		let mut battlefield = battlefield();
+		let mut undo_stack = UndoStack::default();
+		undo_stack.play_card(&mut battlefield, 0, PlayTarget::FriendInsertionSlot(0)).unwrap();
+		let snapshot = Snapshot::take(&mut battlefield);
+		battlefield.random_outcome_count += 1;
+		undo_stack.push(snapshot, &battlefield);
+		assert!(undo_stack.is_empty());
+	}
+
+	#[test]
+	fn turns_are_played_step_by_step() {
+		let mut battlefield = empty_battlefield();
+		let friend = add_creature(&mut battlefield, true, vec![Action::Attack(1)]);
+		let foe = add_creature(&mut battlefield, false, vec![Action::Attack(2)]);
+		let steps = battlefield.play_turn();
+		let actors: Vec<_> = steps.iter().map(|step| step.actor).collect();
+		assert_eq!(actors, [None, Some(friend), Some(foe), None]);
+		// Each step has the hit of its actor.
+		for (step, target, damages) in [(&steps[1], foe, 1), (&steps[2], friend, 2)] {
+			assert!(step.effects.iter().any(|event| matches!(
+				*event,
+				BattleEvent::Damaged { creature, hp_lost, .. }
+					if creature == target && hp_lost == damages
+			)));
+		}
+	}
 }
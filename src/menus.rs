@@ -0,0 +1,838 @@
+//! The scenes that are mostly a few buttons, like the title screen, the pause menu
+//! or the map of the dream between battles.
+
+use ggez::glam::*;
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, Rect, Text};
+use ggez::input::keyboard::KeyInput;
+use ggez::{Context, GameResult};
+
+use crate::audio::Music;
+use crate::input::{key_name, Command};
+use crate::layout::Layout;
+use crate::scene::{Scene, Shared, Transition};
+use crate::settings::Settings;
+use crate::{card_counts, Battle, Run};
+
+/// A title and a column of buttons in the middle of the screen.
+struct Menu<T> {
+	title: String,
+	/// Some text under the title, to tell what happened.
+	subtitle: Option<String>,
+	buttons: Vec<MenuButton<T>>,
+}
+
+struct MenuButton<T> {
+	label: String,
+	what: T,
+	/// Disabled buttons are grayed out and cannot be clicked.
+	enabled: bool,
+}
+
+impl<T: Copy> Menu<T> {
+	fn scale(ctx: &Context) -> f32 {
+		Layout::new(ctx.gfx.size(), ctx.gfx.window().scale_factor() as f32).scale
+	}
+
+	fn button_rects(&self, ctx: &Context) -> Vec<Rect> {
+		let scale = Menu::<T>::scale(ctx);
+		let (width, height) = ctx.gfx.size();
+		let button_dims = (340.0 * scale, 50.0 * scale);
+		let gap = 12.0 * scale;
+		let buttons_height = self.buttons.len() as f32 * (button_dims.1 + gap) - gap;
+		// A bit lower than the center to leave room for the title.
+		let top = (height - buttons_height) / 2.0 + 60.0 * scale;
+		(0..self.buttons.len())
+			.map(|i| {
+				Rect::new(
+					(width - button_dims.0) / 2.0,
+					top + i as f32 * (button_dims.1 + gap),
+					button_dims.0,
+					button_dims.1,
+				)
+			})
+			.collect()
+	}
+
+	/// The button (if any) that would be clicked at the given position.
+	fn button_at(&self, ctx: &Context, pos: Vec2) -> Option<T> {
+		self.button_rects(ctx)
+			.into_iter()
+			.zip(self.buttons.iter())
+			.find(|(rect, button)| button.enabled && rect.contains(pos))
+			.map(|(_rect, button)| button.what)
+	}
+
+	/// Moves the focus (for keyboard and gamepad players) and returns the focused button
+	/// when it is confirmed.
+	fn handle_command(&self, focus: &mut Option<usize>, command: Command) -> Option<T> {
+		let enabled: Vec<usize> =
+			(0..self.buttons.len()).filter(|&i| self.buttons[i].enabled).collect();
+		if enabled.is_empty() {
+			return None;
+		}
+		let position = focus.and_then(|focus| enabled.iter().position(|&i| i == focus));
+		match command {
+			Command::Previous => {
+				let position = position.map_or(enabled.len() - 1, |position| {
+					(position + enabled.len() - 1) % enabled.len()
+				});
+				*focus = Some(enabled[position]);
+				None
+			},
+			Command::Next => {
+				let position = position.map_or(0, |position| (position + 1) % enabled.len());
+				*focus = Some(enabled[position]);
+				None
+			},
+			Command::Confirm => position.map(|position| self.buttons[enabled[position]].what),
+			_ => None,
+		}
+	}
+
+	fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, focus: Option<usize>) -> GameResult {
+		let scale = Menu::<T>::scale(ctx);
+		let width = ctx.gfx.size().0;
+		let button_rects = self.button_rects(ctx);
+		let buttons_top = button_rects.first().map_or(ctx.gfx.size().1 / 2.0, |rect| rect.top());
+
+		let mut title = Text::new(&self.title);
+		title.set_scale(60.0 * scale);
+		let title_dims = Vec2::from(title.measure(ctx)?);
+		let title_pos = Vec2::new((width - title_dims.x) / 2.0, buttons_top - 160.0 * scale);
+		canvas.draw(&title, DrawParam::from(title_pos).color(Color::WHITE));
+		if let Some(subtitle) = &self.subtitle {
+			let mut subtitle = Text::new(subtitle);
+			subtitle.set_scale(26.0 * scale);
+			let subtitle_dims = Vec2::from(subtitle.measure(ctx)?);
+			let subtitle_pos =
+				Vec2::new((width - subtitle_dims.x) / 2.0, buttons_top - 70.0 * scale);
+			canvas.draw(&subtitle, DrawParam::from(subtitle_pos).color(Color::WHITE));
+		}
+
+		let cursor_pos = ctx.mouse.position();
+		for (i, (rect, button)) in button_rects.into_iter().zip(self.buttons.iter()).enumerate() {
+			let hovered = button.enabled && (rect.contains(cursor_pos) || focus == Some(i));
+			let background_color = if hovered {
+				Color::from_rgb(120, 180, 120)
+			} else {
+				Color::from_rgb(80, 130, 80)
+			};
+			let background = Mesh::new_rounded_rectangle(
+				ctx,
+				DrawMode::fill(),
+				rect,
+				10.0 * scale,
+				background_color,
+			)?;
+			canvas.draw(&background, Vec2::new(0.0, 0.0));
+			let mut label = Text::new(&button.label);
+			label.set_scale(26.0 * scale);
+			let label_dims = Vec2::from(label.measure(ctx)?);
+			let label_pos = Vec2::from(rect.center()) - label_dims / 2.0;
+			let label_color =
+				if button.enabled { Color::WHITE } else { Color::from_rgb(150, 150, 150) };
+			canvas.draw(&label, DrawParam::from(label_pos).color(label_color));
+		}
+
+		Ok(())
+	}
+}
+
+#[derive(Clone, Copy)]
+enum TitleButton {
+	NewRun,
+	Continue,
+	Settings,
+	Quit,
+}
+
+/// The first scene of the game.
+pub struct TitleScene {
+	/// When the title screen is reached from a battle or the map (via the pause menu),
+	/// it is still below in the scene stack and can be continued.
+	can_continue: bool,
+	focus: Option<usize>,
+}
+
+impl TitleScene {
+	pub fn new(can_continue: bool) -> TitleScene {
+		TitleScene { can_continue, focus: None }
+	}
+
+	fn menu(&self) -> Menu<TitleButton> {
+		let button = |label: &str, what, enabled| MenuButton {
+			label: label.to_string(),
+			what,
+			enabled,
+		};
+		Menu {
+			title: "Frog Dream".to_string(),
+			subtitle: Some("a frog dreams of battles and friends".to_string()),
+			buttons: vec![
+				button("new run", TitleButton::NewRun, true),
+				button("continue", TitleButton::Continue, self.can_continue),
+				button("settings", TitleButton::Settings, true),
+				button("quit", TitleButton::Quit, true),
+			],
+		}
+	}
+
+	fn activate(&self, button: TitleButton) -> Transition {
+		match button {
+			TitleButton::NewRun => Transition::Push(Box::new(RunSetupScene::new())),
+			TitleButton::Continue => Transition::Pop,
+			TitleButton::Settings => Transition::Push(Box::new(SettingsScene::new())),
+			TitleButton::Quit => Transition::Quit,
+		}
+	}
+}
+
+impl Scene for TitleScene {
+	fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas, _shared: &Shared) -> GameResult {
+		self.menu().draw(ctx, canvas, self.focus)
+	}
+
+	fn music(&self) -> Option<Music> {
+		Some(Music::Dream)
+	}
+
+	fn mouse_button_up_event(
+		&mut self,
+		ctx: &mut Context,
+		_shared: &mut Shared,
+		_button: ggez::event::MouseButton,
+		x: f32,
+		y: f32,
+	) -> GameResult<Transition> {
+		Ok(match self.menu().button_at(ctx, Vec2::new(x, y)) {
+			Some(button) => self.activate(button),
+			None => Transition::None,
+		})
+	}
+
+	fn command_event(
+		&mut self,
+		_ctx: &mut Context,
+		_shared: &mut Shared,
+		command: Command,
+	) -> GameResult<Transition> {
+		Ok(match self.menu().handle_command(&mut self.focus, command) {
+			Some(button) => self.activate(button),
+			None => Transition::None,
+		})
+	}
+}
+
+#[derive(Clone, Copy)]
+enum RunSetupButton {
+	Start,
+	Back,
+}
+
+/// Before a new run, shows what it starts with.
+pub struct RunSetupScene {
+	focus: Option<usize>,
+}
+
+impl RunSetupScene {
+	pub fn new() -> RunSetupScene {
+		RunSetupScene { focus: None }
+	}
+
+	fn menu(&self, shared: &Shared) -> Menu<RunSetupButton> {
+		let button = |label: &str, what| MenuButton { label: label.to_string(), what, enabled: true };
+		let deck = shared.data.deck();
+		Menu {
+			title: "new run".to_string(),
+			subtitle: Some(format!("starting deck: {}", card_counts(deck.iter()))),
+			buttons: vec![
+				button("start dreaming", RunSetupButton::Start),
+				button("back", RunSetupButton::Back),
+			],
+		}
+	}
+
+	fn activate(&self, shared: &Shared, button: RunSetupButton) -> Transition {
+		match button {
+			RunSetupButton::Start => {
+				Transition::ReplaceAll(Box::new(MapScene::new(Run::new(&shared.data))))
+			},
+			RunSetupButton::Back => Transition::Pop,
+		}
+	}
+}
+
+impl Scene for RunSetupScene {
+	fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas, shared: &Shared) -> GameResult {
+		self.menu(shared).draw(ctx, canvas, self.focus)
+	}
+
+	fn mouse_button_up_event(
+		&mut self,
+		ctx: &mut Context,
+		shared: &mut Shared,
+		_button: ggez::event::MouseButton,
+		x: f32,
+		y: f32,
+	) -> GameResult<Transition> {
+		Ok(match self.menu(shared).button_at(ctx, Vec2::new(x, y)) {
+			Some(button) => self.activate(shared, button),
+			None => Transition::None,
+		})
+	}
+
+	fn command_event(
+		&mut self,
+		_ctx: &mut Context,
+		shared: &mut Shared,
+		command: Command,
+	) -> GameResult<Transition> {
+		if let Command::Pause | Command::Cancel = command {
+			return Ok(Transition::Pop);
+		}
+		Ok(match self.menu(shared).handle_command(&mut self.focus, command) {
+			Some(button) => self.activate(shared, button),
+			None => Transition::None,
+		})
+	}
+}
+
+#[derive(Clone, Copy)]
+enum MapButton {
+	NextBattle,
+}
+
+/// The map of the dream, between the battles of a run: the battles already won,
+/// the next one (with its foes) and a few of the ones after it.
+pub struct MapScene {
+	run: Run,
+	focus: Option<usize>,
+}
+
+impl MapScene {
+	/// How many battles after the next one are shown.
+	const BATTLES_AHEAD: u32 = 2;
+
+	pub fn new(run: Run) -> MapScene {
+		MapScene { run, focus: None }
+	}
+
+	fn menu(&self, shared: &Shared) -> Menu<MapButton> {
+		let foes = shared.data.encounter(self.run.battle_number);
+		Menu {
+			title: "dream map".to_string(),
+			subtitle: Some(format!(
+				"battle {}: {} (the deck has {} cards)",
+				self.run.battle_number,
+				card_counts(foes.iter()),
+				self.run.deck.len(),
+			)),
+			buttons: vec![MenuButton {
+				label: format!("enter battle {}", self.run.battle_number),
+				what: MapButton::NextBattle,
+				enabled: true,
+			}],
+		}
+	}
+
+	fn activate(&self, ctx: &Context, shared: &Shared, button: MapButton) -> Transition {
+		match button {
+			MapButton::NextBattle => {
+				Transition::ReplaceAll(Box::new(Battle::new(ctx, shared, self.run.clone())))
+			},
+		}
+	}
+
+	/// The path of the run, one circle per battle, above the menu.
+	fn draw_path(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+		let scale = Menu::<MapButton>::scale(ctx);
+		let (width, height) = ctx.gfx.size();
+		let battle_count = self.run.battle_number + MapScene::BATTLES_AHEAD;
+		let spacing = 90.0 * scale;
+		let radius = 18.0 * scale;
+		let y = height / 2.0 - 230.0 * scale;
+		let left = width / 2.0 - spacing * (battle_count - 1) as f32 / 2.0;
+		let center = |battle_number: u32| Vec2::new(left + spacing * (battle_number - 1) as f32, y);
+		let line_color = Color::from_rgb(150, 150, 150);
+		for battle_number in 1..battle_count {
+			let points = [center(battle_number), center(battle_number + 1)];
+			let line = Mesh::new_line(ctx, &points, 3.0 * scale, line_color)?;
+			canvas.draw(&line, Vec2::new(0.0, 0.0));
+		}
+		for battle_number in 1..=battle_count {
+			let (mode, color) = match battle_number.cmp(&self.run.battle_number) {
+				// Won.
+				std::cmp::Ordering::Less => (DrawMode::fill(), Color::from_rgb(80, 130, 80)),
+				std::cmp::Ordering::Equal => (DrawMode::fill(), Color::from_rgb(120, 180, 120)),
+				std::cmp::Ordering::Greater => (DrawMode::stroke(3.0 * scale), line_color),
+			};
+			let background_color = self.background_color();
+			let center = center(battle_number);
+			// Hides the line under the circles of the battles to come.
+			let background =
+				Mesh::new_circle(ctx, DrawMode::fill(), center, radius, 0.5, background_color)?;
+			canvas.draw(&background, Vec2::new(0.0, 0.0));
+			let circle = Mesh::new_circle(ctx, mode, center, radius, 0.5, color)?;
+			canvas.draw(&circle, Vec2::new(0.0, 0.0));
+			if battle_number == self.run.battle_number {
+				let ring_radius = radius + 6.0 * scale;
+				let ring_mode = DrawMode::stroke(2.0 * scale);
+				let ring = Mesh::new_circle(ctx, ring_mode, center, ring_radius, 0.5, Color::WHITE)?;
+				canvas.draw(&ring, Vec2::new(0.0, 0.0));
+			}
+		}
+		Ok(())
+	}
+}
+
+impl Scene for MapScene {
+	fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas, shared: &Shared) -> GameResult {
+		self.draw_path(ctx, canvas)?;
+		self.menu(shared).draw(ctx, canvas, self.focus)
+	}
+
+	fn music(&self) -> Option<Music> {
+		Some(Music::Dream)
+	}
+
+	fn mouse_button_up_event(
+		&mut self,
+		ctx: &mut Context,
+		shared: &mut Shared,
+		_button: ggez::event::MouseButton,
+		x: f32,
+		y: f32,
+	) -> GameResult<Transition> {
+		Ok(match self.menu(shared).button_at(ctx, Vec2::new(x, y)) {
+			Some(button) => self.activate(ctx, shared, button),
+			None => Transition::None,
+		})
+	}
+
+	fn command_event(
+		&mut self,
+		ctx: &mut Context,
+		shared: &mut Shared,
+		command: Command,
+	) -> GameResult<Transition> {
+		if let Command::Pause = command {
+			return Ok(Transition::Push(Box::new(PauseScene::new())));
+		}
+		Ok(match self.menu(shared).handle_command(&mut self.focus, command) {
+			Some(button) => self.activate(ctx, shared, button),
+			None => Transition::None,
+		})
+	}
+}
+
+#[derive(Clone, Copy)]
+enum PauseButton {
+	Resume,
+	Settings,
+	BackToTitle,
+}
+
+/// Pauses the battle (or the map) below it, opened and closed with Escape.
+pub struct PauseScene {
+	focus: Option<usize>,
+}
+
+impl PauseScene {
+	pub fn new() -> PauseScene {
+		PauseScene { focus: None }
+	}
+
+	fn menu(&self) -> Menu<PauseButton> {
+		let button = |label: &str, what| MenuButton { label: label.to_string(), what, enabled: true };
+		Menu {
+			title: "pause".to_string(),
+			subtitle: None,
+			buttons: vec![
+				button("resume", PauseButton::Resume),
+				button("settings", PauseButton::Settings),
+				button("back to title", PauseButton::BackToTitle),
+			],
+		}
+	}
+
+	fn activate(&self, button: PauseButton) -> Transition {
+		match button {
+			PauseButton::Resume => Transition::Pop,
+			PauseButton::Settings => Transition::Push(Box::new(SettingsScene::new())),
+			// The battle (or map) stays below the title screen so that it can be continued.
+			PauseButton::BackToTitle => Transition::Replace(Box::new(TitleScene::new(true))),
+		}
+	}
+}
+
+impl Scene for PauseScene {
+	fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas, _shared: &Shared) -> GameResult {
+		self.menu().draw(ctx, canvas, self.focus)
+	}
+
+	fn is_overlay(&self) -> bool {
+		true
+	}
+
+	fn mouse_button_up_event(
+		&mut self,
+		ctx: &mut Context,
+		_shared: &mut Shared,
+		_button: ggez::event::MouseButton,
+		x: f32,
+		y: f32,
+	) -> GameResult<Transition> {
+		Ok(match self.menu().button_at(ctx, Vec2::new(x, y)) {
+			Some(button) => self.activate(button),
+			None => Transition::None,
+		})
+	}
+
+	fn command_event(
+		&mut self,
+		_ctx: &mut Context,
+		_shared: &mut Shared,
+		command: Command,
+	) -> GameResult<Transition> {
+		if let Command::Pause | Command::Cancel = command {
+			return Ok(Transition::Pop);
+		}
+		Ok(match self.menu().handle_command(&mut self.focus, command) {
+			Some(button) => self.activate(button),
+			None => Transition::None,
+		})
+	}
+}
+
+#[derive(Clone, Copy)]
+enum SettingsButton {
+	EdgePanning,
+	AnimationSpeed,
+	MasterVolume,
+	MusicVolume,
+	SfxVolume,
+	KeyBinding(Command),
+	Back,
+}
+
+pub struct SettingsScene {
+	focus: Option<usize>,
+	/// After a key binding button is clicked, the next key pressed gets bound to its command.
+	waiting_for_key: Option<Command>,
+}
+
+impl SettingsScene {
+	pub fn new() -> SettingsScene {
+		SettingsScene { focus: None, waiting_for_key: None }
+	}
+
+	fn menu(&self, shared: &Shared) -> Menu<SettingsButton> {
+		let on_off = |setting: bool| if setting { "on" } else { "off" };
+		let button = |label: String, what| MenuButton { label, what, enabled: true };
+		let mut buttons = vec![
+			button(
+				format!("edge panning: {}", on_off(shared.settings.edge_panning)),
+				SettingsButton::EdgePanning,
+			),
+			button(
+				format!("animation speed: {}", shared.settings.animation_speed.name()),
+				SettingsButton::AnimationSpeed,
+			),
+			button(
+				format!("master volume: {}%", shared.settings.master_volume),
+				SettingsButton::MasterVolume,
+			),
+			button(
+				format!("music volume: {}%", shared.settings.music_volume),
+				SettingsButton::MusicVolume,
+			),
+			button(
+				format!("sound effects volume: {}%", shared.settings.sfx_volume),
+				SettingsButton::SfxVolume,
+			),
+		];
+		for command in Command::ALL {
+			let keys: Vec<String> =
+				shared.settings.key_bindings.keys(command).map(key_name).collect();
+			let keys = if self.waiting_for_key == Some(command) {
+				"...".to_string()
+			} else {
+				keys.join(", ")
+			};
+			buttons.push(button(
+				format!("{}: {keys}", command.name()),
+				SettingsButton::KeyBinding(command),
+			));
+		}
+		buttons.push(button("back".to_string(), SettingsButton::Back));
+		Menu {
+			title: "settings".to_string(),
+			subtitle: self
+				.waiting_for_key
+				.map(|command| format!("press a key for \"{}\"", command.name())),
+			buttons,
+		}
+	}
+
+	fn activate(
+		&mut self,
+		ctx: &Context,
+		shared: &mut Shared,
+		button: SettingsButton,
+	) -> GameResult<Transition> {
+		match button {
+			SettingsButton::EdgePanning => {
+				shared.settings.edge_panning = !shared.settings.edge_panning;
+				shared.settings.save(ctx)?;
+			},
+			SettingsButton::AnimationSpeed => {
+				shared.settings.animation_speed = shared.settings.animation_speed.next();
+				shared.settings.save(ctx)?;
+			},
+			SettingsButton::MasterVolume => {
+				shared.settings.master_volume = Settings::next_volume(shared.settings.master_volume);
+				shared.settings.save(ctx)?;
+			},
+			SettingsButton::MusicVolume => {
+				shared.settings.music_volume = Settings::next_volume(shared.settings.music_volume);
+				shared.settings.save(ctx)?;
+			},
+			SettingsButton::SfxVolume => {
+				shared.settings.sfx_volume = Settings::next_volume(shared.settings.sfx_volume);
+				shared.settings.save(ctx)?;
+			},
+			SettingsButton::KeyBinding(command) => self.waiting_for_key = Some(command),
+			SettingsButton::Back => return Ok(Transition::Pop),
+		}
+		Ok(Transition::None)
+	}
+}
+
+impl Scene for SettingsScene {
+	fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas, shared: &Shared) -> GameResult {
+		self.menu(shared).draw(ctx, canvas, self.focus)
+	}
+
+	fn mouse_button_up_event(
+		&mut self,
+		ctx: &mut Context,
+		shared: &mut Shared,
+		_button: ggez::event::MouseButton,
+		x: f32,
+		y: f32,
+	) -> GameResult<Transition> {
+		match self.menu(shared).button_at(ctx, Vec2::new(x, y)) {
+			Some(button) => self.activate(ctx, shared, button),
+			None => Ok(Transition::None),
+		}
+	}
+
+	fn key_down_event(
+		&mut self,
+		ctx: &mut Context,
+		shared: &mut Shared,
+		input: KeyInput,
+	) -> GameResult<Transition> {
+		if let (Some(command), Some(key)) = (self.waiting_for_key, input.keycode) {
+			// The keys bound to pause or cancel cancel the rebinding instead of being bound.
+			let bindings = &shared.settings.key_bindings;
+			let cancels = [Command::Pause, Command::Cancel]
+				.into_iter()
+				.any(|cancel_command| bindings.keys(cancel_command).any(|bound| bound == key));
+			if cancels {
+				self.waiting_for_key = None;
+				return Ok(Transition::None);
+			}
+			shared.settings.key_bindings.rebind(command, key);
+			shared.settings.save(ctx)?;
+			self.waiting_for_key = None;
+			return Ok(Transition::None);
+		}
+		match input.keycode.and_then(|key| shared.settings.key_bindings.command(key)) {
+			Some(command) => self.command_event(ctx, shared, command),
+			None => Ok(Transition::None),
+		}
+	}
+
+	fn command_event(
+		&mut self,
+		ctx: &mut Context,
+		shared: &mut Shared,
+		command: Command,
+	) -> GameResult<Transition> {
+		if let Command::Pause | Command::Cancel = command {
+			if self.waiting_for_key.take().is_none() {
+				return Ok(Transition::Pop);
+			}
+			return Ok(Transition::None);
+		}
+		match self.menu(shared).handle_command(&mut self.focus, command) {
+			Some(button) => self.activate(ctx, shared, button),
+			None => Ok(Transition::None),
+		}
+	}
+}
+
+#[derive(Clone, Copy)]
+enum GameOverButton {
+	WakeUp,
+}
+
+/// When all the friends fled and there is no creature card left to play,
+/// the dream turns into a nightmare and the run is over.
+pub struct GameOverScene {
+	battles_won: u32,
+	focus: Option<usize>,
+}
+
+impl GameOverScene {
+	pub fn new(battles_won: u32) -> GameOverScene {
+		GameOverScene { battles_won, focus: None }
+	}
+
+	fn menu(&self) -> Menu<GameOverButton> {
+		Menu {
+			title: "nightmare".to_string(),
+			subtitle: Some(format!(
+				"all the friends fled, after {} won battles",
+				self.battles_won
+			)),
+			buttons: vec![MenuButton {
+				label: "wake up".to_string(),
+				what: GameOverButton::WakeUp,
+				enabled: true,
+			}],
+		}
+	}
+
+	fn activate(&self, button: GameOverButton) -> Transition {
+		match button {
+			GameOverButton::WakeUp => Transition::ReplaceAll(Box::new(TitleScene::new(false))),
+		}
+	}
+}
+
+impl Scene for GameOverScene {
+	fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas, _shared: &Shared) -> GameResult {
+		self.menu().draw(ctx, canvas, self.focus)
+	}
+
+	fn music(&self) -> Option<Music> {
+		Some(Music::Nightmare)
+	}
+
+	fn is_overlay(&self) -> bool {
+		true
+	}
+
+	fn mouse_button_up_event(
+		&mut self,
+		ctx: &mut Context,
+		_shared: &mut Shared,
+		_button: ggez::event::MouseButton,
+		x: f32,
+		y: f32,
+	) -> GameResult<Transition> {
+		Ok(match self.menu().button_at(ctx, Vec2::new(x, y)) {
+			Some(button) => self.activate(button),
+			None => Transition::None,
+		})
+	}
+
+	fn command_event(
+		&mut self,
+		_ctx: &mut Context,
+		_shared: &mut Shared,
+		command: Command,
+	) -> GameResult<Transition> {
+		Ok(match self.menu().handle_command(&mut self.focus, command) {
+			Some(button) => self.activate(button),
+			None => Transition::None,
+		})
+	}
+}
+
+#[derive(Clone, Copy)]
+enum VictoryButton {
+	DreamOn,
+	WakeUp,
+}
+
+/// When no foe is left on the battlefield (they all fled or became friends).
+/// The run goes on with the map and then the next battle, keeping the deck (and the new
+/// friends in it).
+pub struct VictoryScene {
+	next_run: Run,
+	focus: Option<usize>,
+}
+
+impl VictoryScene {
+	pub fn new(next_run: Run) -> VictoryScene {
+		VictoryScene { next_run, focus: None }
+	}
+
+	fn menu(&self) -> Menu<VictoryButton> {
+		let button = |label: &str, what| MenuButton { label: label.to_string(), what, enabled: true };
+		Menu {
+			title: "victory".to_string(),
+			subtitle: Some(format!(
+				"the deck now has {} cards, next is battle {}",
+				self.next_run.deck.len(),
+				self.next_run.battle_number,
+			)),
+			buttons: vec![
+				button("dream on", VictoryButton::DreamOn),
+				button("wake up", VictoryButton::WakeUp),
+			],
+		}
+	}
+
+	fn activate(&self, button: VictoryButton) -> Transition {
+		match button {
+			VictoryButton::DreamOn => {
+				Transition::ReplaceAll(Box::new(MapScene::new(self.next_run.clone())))
+			},
+			VictoryButton::WakeUp => Transition::ReplaceAll(Box::new(TitleScene::new(false))),
+		}
+	}
+}
+
+impl Scene for VictoryScene {
+	fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas, _shared: &Shared) -> GameResult {
+		self.menu().draw(ctx, canvas, self.focus)
+	}
+
+	fn music(&self) -> Option<Music> {
+		Some(Music::Dream)
+	}
+
+	fn is_overlay(&self) -> bool {
+		true
+	}
+
+	fn mouse_button_up_event(
+		&mut self,
+		ctx: &mut Context,
+		_shared: &mut Shared,
+		_button: ggez::event::MouseButton,
+		x: f32,
+		y: f32,
+	) -> GameResult<Transition> {
+		Ok(match self.menu().button_at(ctx, Vec2::new(x, y)) {
+			Some(button) => self.activate(button),
+			None => Transition::None,
+		})
+	}
+
+	fn command_event(
+		&mut self,
+		_ctx: &mut Context,
+		_shared: &mut Shared,
+		command: Command,
+	) -> GameResult<Transition> {
+		Ok(match self.menu().handle_command(&mut self.focus, command) {
+			Some(button) => self.activate(button),
+			None => Transition::None,
+		})
+	}
+}
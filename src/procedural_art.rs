@@ -0,0 +1,234 @@
+//! Card art drawn from a few parameters instead of a sprite, for the dreamy phantasmagoric
+//! creatures (geometric shapes, incarnations of concepts, etc.) that do not need to be
+//! hand-drawn. It is also what creatures look like when their sprite is missing.
+
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use ggez::glam::*;
+use ggez::graphics::{Canvas, Color, DrawMode, Mesh, MeshData, Vertex};
+use ggez::{Context, GameResult};
+
+use crate::spritesheet::Pose;
+
+#[derive(Clone, Copy)]
+pub struct ProceduralArt {
+	/// The number of sides of the body, a circle if less than 3.
+	pub sides: u32,
+	/// The body is filled with a gradient, from `color` at its center to `edge_color`.
+	pub color: Color,
+	pub edge_color: Color,
+	/// Rings around the body, that pulse with it.
+	pub rings: u32,
+	/// Pulses per second (0 for a still body).
+	pub pulse: f32,
+	/// Turns per second (negative to turn the other way).
+	pub spin: f32,
+	pub eyes: u32,
+}
+
+/// A fully saturated color of the given hue (from 0 to 1), lightened by `lightness`
+/// (0 for the pure color, 1 for white).
+fn hue_color(hue: f32, lightness: f32) -> Color {
+	let channel = |offset: f32| {
+		let x = ((hue + offset).fract() * 6.0 - 3.0).abs() - 1.0;
+		let pure = x.clamp(0.0, 1.0);
+		pure + (1.0 - pure) * lightness
+	};
+	Color::new(channel(0.0), channel(2.0 / 3.0), channel(1.0 / 3.0), 1.0)
+}
+
+impl ProceduralArt {
+	/// The most sides, rings or eyes an art can have, more would not be readable anyway.
+	const MAX_COUNT: u32 = 16;
+
+	/// Parses parameters like `sides 5, color 0.8 0.6 1.0, edge 0.3 0.2 0.6, rings 2,
+	/// pulse 1, spin 0.1, eyes 1`. The parameters that are not given are taken from
+	/// `ProceduralArt::from_seed` with the given seed.
+	pub fn parse(text: &str, seed: &str) -> Result<ProceduralArt, String> {
+		let mut art = ProceduralArt::from_seed(seed);
+		for part in text.split(',') {
+			let mut words = part.split_whitespace();
+			let Some(name) = words.next() else {
+				continue;
+			};
+			let numbers: Vec<f32> = words
+				.map(|number| number.parse().ok().filter(|number: &f32| number.is_finite()))
+				.collect::<Option<_>>()
+				.ok_or_else(|| format!("\"{}\" is not made of numbers", part.trim()))?;
+			let expected_count = match name {
+				"color" | "edge" => 3,
+				_ => 1,
+			};
+			if numbers.len() != expected_count {
+				return Err(format!("expected {expected_count} numbers after \"{name}\""));
+			}
+			let count = || {
+				let number = numbers[0];
+				if number.fract() == 0.0 && (0.0..=ProceduralArt::MAX_COUNT as f32).contains(&number) {
+					Ok(number as u32)
+				} else {
+					let max = ProceduralArt::MAX_COUNT;
+					Err(format!("\"{name}\" must be a whole number from 0 to {max}"))
+				}
+			};
+			let color = || {
+				if numbers.iter().all(|component| (0.0..=1.0).contains(component)) {
+					Ok(Color::new(numbers[0], numbers[1], numbers[2], 1.0))
+				} else {
+					Err(format!("the components of \"{name}\" must be from 0 to 1"))
+				}
+			};
+			match name {
+				"sides" => art.sides = count()?,
+				"color" => art.color = color()?,
+				"edge" => art.edge_color = color()?,
+				"rings" => art.rings = count()?,
+				"pulse" if numbers[0] < 0.0 => return Err("\"pulse\" cannot be negative".to_string()),
+				"pulse" => art.pulse = numbers[0],
+				"spin" => art.spin = numbers[0],
+				"eyes" => art.eyes = count()?,
+				_ => return Err(format!("unknown art parameter \"{name}\"")),
+			}
+		}
+		Ok(art)
+	}
+
+	/// Some art that always looks the same for the same seed (like the name of a card),
+	/// but different for different seeds.
+	pub fn from_seed(seed: &str) -> ProceduralArt {
+		// FNV-1a, any hash would do as long as it does not change between runs.
+		let mut hash: u32 = 0x811c9dc5;
+		for byte in seed.bytes() {
+			hash = (hash ^ byte as u32).wrapping_mul(0x01000193);
+		}
+		let mut bits = |count: u32| {
+			let value = hash % count;
+			hash /= count;
+			value
+		};
+		let hue = bits(360) as f32 / 360.0;
+		ProceduralArt {
+			sides: [0, 3, 4, 5, 6, 8][bits(6) as usize],
+			color: hue_color(hue, 0.6),
+			edge_color: hue_color((hue + 0.1).fract(), 0.1),
+			rings: bits(3),
+			pulse: 0.5 + bits(4) as f32 * 0.25,
+			spin: (bits(5) as f32 - 2.0) * 0.05,
+			eyes: 1 + bits(2),
+		}
+	}
+
+	/// Draws the art centered on `center`, with the body about `radius` big.
+	/// `time` drives the pulsing and spinning (usually the game clock time).
+	pub fn draw(
+		&self,
+		ctx: &mut Context,
+		canvas: &mut Canvas,
+		center: Vec2,
+		radius: f32,
+		pose: Pose,
+		time: Duration,
+	) -> GameResult {
+		let time = time.as_secs_f32();
+		let pose_scale = match pose {
+			Pose::Idle => 1.0,
+			Pose::Attack => 1.15,
+			Pose::Hurt => 0.85,
+		};
+		let pulse = 1.0 + 0.06 * (time * self.pulse * TAU).sin();
+		let radius = radius * pose_scale * pulse;
+
+		// The rings, fainter the further they are.
+		for i in 0..self.rings {
+			let ring_radius = radius * (1.2 + 0.18 * i as f32);
+			let alpha = 0.6 / (i + 1) as f32;
+			let color = Color { a: alpha, ..self.edge_color };
+			let ring = Mesh::new_circle(ctx, DrawMode::stroke(2.0), center, ring_radius, 0.5, color)?;
+			canvas.draw(&ring, Vec2::new(0.0, 0.0));
+		}
+
+		// The body, a fan of triangles from its center to its outline so that the colors
+		// of the vertices make a radial gradient.
+		let sides = if self.sides < 3 { 32 } else { self.sides };
+		let angle = time * self.spin * TAU;
+		let mut vertices = vec![Vertex {
+			position: center.into(),
+			uv: [0.5, 0.5],
+			color: self.color.into(),
+		}];
+		let mut indices = vec![];
+		for i in 0..sides {
+			let corner_angle = angle + i as f32 / sides as f32 * TAU;
+			let position = center + Vec2::new(corner_angle.sin(), -corner_angle.cos()) * radius;
+			vertices.push(Vertex {
+				position: position.into(),
+				uv: [0.5, 0.5],
+				color: self.edge_color.into(),
+			});
+			indices.extend([0, i + 1, (i + 1) % sides + 1]);
+		}
+		let body = Mesh::from_data(ctx, MeshData { vertices: &vertices, indices: &indices });
+		canvas.draw(&body, Vec2::new(0.0, 0.0));
+
+		// The eyes do not spin with the body, they always look at the player.
+		let eye_radius = radius * 0.16;
+		for i in 0..self.eyes {
+			let spread = i as f32 - (self.eyes - 1) as f32 / 2.0;
+			let eye_center = center + Vec2::new(spread * eye_radius * 2.6, -radius * 0.1);
+			for (size, color) in [(1.0, Color::WHITE), (0.5, Color::BLACK)] {
+				let circle_radius = eye_radius * size;
+				let circle =
+					Mesh::new_circle(ctx, DrawMode::fill(), eye_center, circle_radius, 0.2, color)?;
+				canvas.draw(&circle, Vec2::new(0.0, 0.0));
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_the_given_parameters() {
+		let art = ProceduralArt::parse("sides 5, color 1 0.8 1, edge 0.5 0.2 0.8, rings 2", "x")
+			.unwrap();
+		assert_eq!((art.sides, art.rings), (5, 2));
+		assert_eq!(art.color, Color::new(1.0, 0.8, 1.0, 1.0));
+		assert_eq!(art.edge_color, Color::new(0.5, 0.2, 0.8, 1.0));
+		let art = ProceduralArt::parse("pulse 0, spin -0.5, eyes 0", "x").unwrap();
+		assert_eq!((art.pulse, art.spin, art.eyes), (0.0, -0.5, 0));
+	}
+
+	#[test]
+	fn missing_parameters_come_from_the_seed() {
+		let seeded = ProceduralArt::from_seed("fwog");
+		let art = ProceduralArt::parse("eyes 3", "fwog").unwrap();
+		assert_eq!(art.eyes, 3);
+		assert_eq!((art.sides, art.rings, art.color), (seeded.sides, seeded.rings, seeded.color));
+		let empty = ProceduralArt::parse("", "fwog").unwrap();
+		assert_eq!((empty.sides, empty.eyes), (seeded.sides, seeded.eyes));
+	}
+
+	#[test]
+	fn reports_errors() {
+		for (text, error) in [
+			("wings 2", "unknown art parameter \"wings\""),
+			("sides", "expected 1 numbers after \"sides\""),
+			("color 1 1", "expected 3 numbers after \"color\""),
+			("eyes two", "\"eyes two\" is not made of numbers"),
+			("spin inf", "\"spin inf\" is not made of numbers"),
+			("sides -3", "\"sides\" must be a whole number from 0 to 16"),
+			("rings 1.5", "\"rings\" must be a whole number from 0 to 16"),
+			("eyes 100", "\"eyes\" must be a whole number from 0 to 16"),
+			("color 1 2 0", "the components of \"color\" must be from 0 to 1"),
+			("edge -0.1 0 0", "the components of \"edge\" must be from 0 to 1"),
+			("pulse -1", "\"pulse\" cannot be negative"),
+		] {
+			let error_found = ProceduralArt::parse(text, "x").err();
+			assert_eq!(error_found.as_deref(), Some(error), "{text}");
+		}
+	}
+}
@@ -0,0 +1,268 @@
+//! Rich text, for the rules text of cards, the tooltips and the combat log.
+//!
+//! The text is written with a small markup:
+//! - `{food}` is an inline icon, the food sprite (or the icon of a status, like `{scary}`),
+//! - `[heal]some text[/]` gives a color to the text in between (see `color_by_name`),
+//! - anything else is just text (tags that are not known are displayed as they are).
+//!
+//! It is laid out with word wrap to fit in a given width, icons are as big as the text.
+
+use ggez::glam::*;
+use ggez::graphics::{Canvas, Color, DrawParam, Text};
+use ggez::{Context, GameResult};
+
+use crate::spritesheet::Spritesheet;
+use crate::{draw_status_icon, Action, CardSpec, Status};
+
+#[derive(Clone, Copy)]
+enum Icon {
+	/// A sprite of the spritesheet, by name.
+	Sprite(&'static str),
+	Status(Status),
+}
+
+fn icon_by_name(name: &str) -> Option<Icon> {
+	match name {
+		"food" => Some(Icon::Sprite(CardSpec::Food.id())),
+		"scared" => Some(Icon::Status(Status::Scared)),
+		"scary" => Some(Icon::Status(Status::Scary)),
+		"night" => Some(Icon::Status(Status::Night)),
+		"anger" => Some(Icon::Status(Status::Anger)),
+		_ => None,
+	}
+}
+
+/// The colors that can be used in `[name]...[/]` spans, the same as elsewhere in the interface.
+fn color_by_name(name: &str) -> Option<Color> {
+	match name {
+		"attack" => Some(Action::Attack(0).color()),
+		"block" => Some(Action::Defend(0).color()),
+		"heal" => Some(Action::Heal(0).color()),
+		"hp" => Some(Color::from_rgb(255, 150, 180)),
+		"gray" => Some(Color::from_rgb(150, 150, 150)),
+		"food" => Some(Status::Food.color()),
+		"scared" => Some(Status::Scared.color()),
+		"scary" => Some(Status::Scary.color()),
+		"night" => Some(Status::Night.color()),
+		"anger" => Some(Status::Anger.color()),
+		_ => None,
+	}
+}
+
+enum Token {
+	Word { text: String, color: Option<Color> },
+	Icon { name: String, icon: Icon },
+	Space,
+	Newline,
+}
+
+/// If `text` starts with a tag like `{name}` (with the given delimiters),
+/// returns the name and what comes after the tag.
+fn tag_at(text: &str, open: char, close: char) -> Option<(&str, &str)> {
+	let inside = text.strip_prefix(open)?;
+	let end = inside.find(close)?;
+	let name = &inside[..end];
+	let is_name = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '/');
+	is_name.then(|| (name, &inside[end + close.len_utf8()..]))
+}
+
+fn parse(markup: &str) -> Vec<Token> {
+	let mut tokens = vec![];
+	let mut word = String::new();
+	let mut color = None;
+	let flush = |tokens: &mut Vec<Token>, word: &mut String, color: Option<Color>| {
+		if !word.is_empty() {
+			tokens.push(Token::Word { text: std::mem::take(word), color });
+		}
+	};
+	let mut rest = markup;
+	while let Some(c) = rest.chars().next() {
+		if let Some((name, after)) = tag_at(rest, '{', '}') {
+			if let Some(icon) = icon_by_name(name) {
+				flush(&mut tokens, &mut word, color);
+				tokens.push(Token::Icon { name: name.to_string(), icon });
+				rest = after;
+				continue;
+			}
+		}
+		if let Some((name, after)) = tag_at(rest, '[', ']') {
+			let new_color = if name == "/" { Some(None) } else { color_by_name(name).map(Some) };
+			if let Some(new_color) = new_color {
+				flush(&mut tokens, &mut word, color);
+				color = new_color;
+				rest = after;
+				continue;
+			}
+		}
+		rest = &rest[c.len_utf8()..];
+		match c {
+			' ' => {
+				flush(&mut tokens, &mut word, color);
+				tokens.push(Token::Space);
+			},
+			'\n' => {
+				flush(&mut tokens, &mut word, color);
+				tokens.push(Token::Newline);
+			},
+			_ => word.push(c),
+		}
+	}
+	flush(&mut tokens, &mut word, color);
+	tokens
+}
+
+/// The text without the markup, icons being replaced by their names
+/// (for the exported combat log).
+pub fn plain(markup: &str) -> String {
+	parse(markup)
+		.into_iter()
+		.map(|token| match token {
+			Token::Word { text, .. } => text,
+			Token::Icon { name, .. } => name,
+			Token::Space => " ".to_string(),
+			Token::Newline => "\n".to_string(),
+		})
+		.collect()
+}
+
+enum Piece {
+	Text { text: Text, color: Option<Color> },
+	Icon(Icon),
+}
+
+/// Some markup, laid out and ready to be drawn.
+pub struct RichText {
+	/// The pieces, with where they are relative to the top left corner of the text.
+	pieces: Vec<(Vec2, Piece)>,
+	size: f32,
+	dims: Vec2,
+}
+
+impl RichText {
+	/// Lays out the markup with the given text size, wrapping the lines
+	/// that would be wider than `max_width`.
+	pub fn new(ctx: &Context, markup: &str, size: f32, max_width: f32) -> GameResult<RichText> {
+		let space_width = size * 0.3;
+		let line_height = size * 1.2;
+		let mut pieces = vec![];
+		let mut pos = Vec2::new(0.0, 0.0);
+		let mut width: f32 = 0.0;
+		// Words and icons that are not separated by spaces are kept on the same line.
+		let mut chunk: Vec<(f32, Piece)> = vec![];
+		let mut space_before = false;
+		let mut place_chunk = |chunk: &mut Vec<(f32, Piece)>, pos: &mut Vec2, space_before: bool| {
+			if chunk.is_empty() {
+				return;
+			}
+			let chunk_width: f32 = chunk.iter().map(|(piece_width, _piece)| piece_width).sum();
+			let mut advance = if space_before && pos.x > 0.0 { space_width } else { 0.0 };
+			if pos.x > 0.0 && pos.x + advance + chunk_width > max_width {
+				*pos = Vec2::new(0.0, pos.y + line_height);
+				advance = 0.0;
+			}
+			pos.x += advance;
+			for (piece_width, piece) in chunk.drain(..) {
+				pieces.push((*pos, piece));
+				pos.x += piece_width;
+			}
+			width = width.max(pos.x);
+		};
+		for token in parse(markup) {
+			match token {
+				Token::Word { text, color } => {
+					let mut text = Text::new(text);
+					text.set_scale(size);
+					let text_width = Vec2::from(text.measure(ctx)?).x;
+					chunk.push((text_width, Piece::Text { text, color }));
+				},
+				Token::Icon { icon, .. } => chunk.push((size, Piece::Icon(icon))),
+				Token::Space => {
+					place_chunk(&mut chunk, &mut pos, space_before);
+					space_before = true;
+				},
+				Token::Newline => {
+					place_chunk(&mut chunk, &mut pos, space_before);
+					pos = Vec2::new(0.0, pos.y + line_height);
+					space_before = false;
+				},
+			}
+		}
+		place_chunk(&mut chunk, &mut pos, space_before);
+		let dims = Vec2::new(width, pos.y + line_height);
+		Ok(RichText { pieces, size, dims })
+	}
+
+	/// The width and height the text takes.
+	pub fn dims(&self) -> Vec2 {
+		self.dims
+	}
+
+	/// Draws the text with its top left corner at `pos`,
+	/// `color` is for the text that is not in a color span.
+	pub fn draw(
+		&self,
+		ctx: &mut Context,
+		canvas: &mut Canvas,
+		spritesheet: &Spritesheet,
+		pos: Vec2,
+		color: Color,
+	) -> GameResult {
+		for (offset, piece) in self.pieces.iter() {
+			let piece_pos = pos + *offset;
+			match piece {
+				Piece::Text { text, color: span_color } => {
+					canvas.draw(text, DrawParam::from(piece_pos).color(span_color.unwrap_or(color)));
+				},
+				Piece::Icon(icon) => {
+					let center = piece_pos + Vec2::new(self.size / 2.0, self.size * 0.55);
+					match icon {
+						Icon::Sprite(name) => {
+							spritesheet.draw_fit(ctx, canvas, name, center, self.size)?
+						},
+						// The status icons are drawn about 32 pixels wide.
+						Icon::Status(status) => {
+							draw_status_icon(ctx, canvas, *status, center, self.size / 32.0)?
+						},
+					}
+				},
+			}
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn plain_replaces_icons_by_their_names() {
+		assert_eq!(plain("give {food} [attack]3 damage[/] now"), "give food 3 damage now");
+		assert_eq!(plain("line\n{scary}x"), "line\nscaryx");
+	}
+
+	#[test]
+	fn unknown_tags_are_text() {
+		assert_eq!(plain("{nope} [nope]x[/] {} [] {a b}"), "{nope} [nope]x {} [] {a b}");
+	}
+
+	#[test]
+	fn icons_stick_to_the_words_around_them() {
+		let tokens = parse("a{food}b c");
+		assert_eq!(tokens.len(), 5);
+		assert!(matches!(&tokens[0], Token::Word { text, .. } if text == "a"));
+		assert!(matches!(&tokens[1], Token::Icon { name, icon: Icon::Sprite(_) } if name == "food"));
+		assert!(matches!(&tokens[2], Token::Word { text, .. } if text == "b"));
+		assert!(matches!(tokens[3], Token::Space));
+		assert!(matches!(&tokens[4], Token::Word { text, .. } if text == "c"));
+	}
+
+	#[test]
+	fn color_spans_color_the_words_in_between() {
+		let tokens = parse("[heal]x y[/] z");
+		let heal = Some(Action::Heal(0).color());
+		assert!(matches!(&tokens[0], Token::Word { text, color } if text == "x" && *color == heal));
+		assert!(matches!(&tokens[2], Token::Word { text, color } if text == "y" && *color == heal));
+		assert!(matches!(&tokens[4], Token::Word { text, color: None } if text == "z"));
+	}
+}
@@ -0,0 +1,321 @@
+//! Scenes (the title screen, a battle, the pause menu, etc.) and the stack of scenes
+//! that the game is made of. Only the scene at the top of the stack gets the inputs,
+//! but overlay scenes (like the pause menu) let the scenes below them be seen.
+
+use ggez::glam::*;
+use ggez::graphics::{Canvas, Color, DrawMode, Mesh, Rect};
+use ggez::input::keyboard::KeyInput;
+use ggez::{Context, GameResult};
+
+use crate::audio::{Audio, Music};
+use crate::clock::GameClock;
+use crate::game_data::GameData;
+use crate::hot_reload::HotReload;
+use crate::input::Command;
+use crate::settings::Settings;
+use crate::spritesheet::Spritesheet;
+use crate::toasts::Toasts;
+
+/// What the scene stack has to do after a scene handled an event.
+pub enum Transition {
+	None,
+	/// Puts a scene on top of the current one (that will get back the inputs
+	/// when the new scene is popped).
+	Push(Box<dyn Scene>),
+	/// Removes the current scene, the stack quits the game if it was the last one.
+	Pop,
+	/// Replaces the current scene, the scenes below are kept.
+	Replace(Box<dyn Scene>),
+	/// Replaces all the scenes of the stack, for when the scenes below
+	/// make no sense anymore (like a finished battle).
+	ReplaceAll(Box<dyn Scene>),
+	Quit,
+}
+
+/// What is shared by all the scenes.
+pub struct Shared {
+	pub spritesheet: Spritesheet,
+	pub data: GameData,
+	pub settings: Settings,
+	/// Animations are timed with it, see `TimeProgression`.
+	pub clock: GameClock,
+	/// Drawn on top of every scene.
+	pub toasts: Toasts,
+	pub audio: Audio,
+}
+
+/// A scene of the game. Every event has a default implementation that does nothing
+/// so that scenes only have to handle what they care about.
+pub trait Scene {
+	fn update(&mut self, _ctx: &mut Context, _shared: &mut Shared) -> GameResult<Transition> {
+		Ok(Transition::None)
+	}
+
+	fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas, shared: &Shared) -> GameResult;
+
+	/// Overlay scenes are drawn on top of the scenes below them (that get darkened a bit),
+	/// while other scenes hide everything below.
+	fn is_overlay(&self) -> bool {
+		false
+	}
+
+	/// The music that plays while the scene is on top, `None` keeps the music
+	/// of the scenes below (like for the pause menu).
+	fn music(&self) -> Option<Music> {
+		None
+	}
+
+	/// The color the screen gets cleared with, only used for non-overlay scenes.
+	fn background_color(&self) -> Color {
+		Color::from([0.1, 0.2, 0.3, 1.0])
+	}
+
+	fn mouse_motion_event(
+		&mut self,
+		_ctx: &mut Context,
+		_shared: &mut Shared,
+		_x: f32,
+		_y: f32,
+	) -> GameResult<Transition> {
+		Ok(Transition::None)
+	}
+
+	fn mouse_button_down_event(
+		&mut self,
+		_ctx: &mut Context,
+		_shared: &mut Shared,
+		_button: ggez::event::MouseButton,
+		_x: f32,
+		_y: f32,
+	) -> GameResult<Transition> {
+		Ok(Transition::None)
+	}
+
+	fn mouse_button_up_event(
+		&mut self,
+		_ctx: &mut Context,
+		_shared: &mut Shared,
+		_button: ggez::event::MouseButton,
+		_x: f32,
+		_y: f32,
+	) -> GameResult<Transition> {
+		Ok(Transition::None)
+	}
+
+	fn mouse_wheel_event(
+		&mut self,
+		_ctx: &mut Context,
+		_shared: &mut Shared,
+		_x: f32,
+		_y: f32,
+	) -> GameResult<Transition> {
+		Ok(Transition::None)
+	}
+
+	/// By default, keys are translated into commands (according to the key bindings).
+	fn key_down_event(
+		&mut self,
+		ctx: &mut Context,
+		shared: &mut Shared,
+		input: KeyInput,
+	) -> GameResult<Transition> {
+		match input.keycode.and_then(|key| shared.settings.key_bindings.command(key)) {
+			Some(command) => self.command_event(ctx, shared, command),
+			None => Ok(Transition::None),
+		}
+	}
+
+	/// A command from the keyboard or a gamepad.
+	fn command_event(
+		&mut self,
+		_ctx: &mut Context,
+		_shared: &mut Shared,
+		_command: Command,
+	) -> GameResult<Transition> {
+		Ok(Transition::None)
+	}
+
+	/// Unlike the other events, all the scenes of the stack get this one
+	/// (so that scenes below are still laid out correctly when they get back on top).
+	fn resize_event(&mut self, _ctx: &mut Context, _width: f32, _height: f32) -> GameResult {
+		Ok(())
+	}
+}
+
+pub struct SceneStack {
+	scenes: Vec<Box<dyn Scene>>,
+	shared: Shared,
+	/// Only in dev mode.
+	hot_reload: Option<HotReload>,
+}
+
+impl SceneStack {
+	pub fn new(
+		first_scene: Box<dyn Scene>,
+		shared: Shared,
+		hot_reload: Option<HotReload>,
+	) -> SceneStack {
+		SceneStack { scenes: vec![first_scene], shared, hot_reload }
+	}
+
+	fn apply(&mut self, ctx: &mut Context, transition: Transition) {
+		match transition {
+			Transition::None => {},
+			Transition::Push(scene) => self.scenes.push(scene),
+			Transition::Pop => {
+				self.scenes.pop();
+			},
+			Transition::Replace(scene) => {
+				self.scenes.pop();
+				self.scenes.push(scene);
+			},
+			Transition::ReplaceAll(scene) => self.scenes = vec![scene],
+			Transition::Quit => self.scenes.clear(),
+		}
+		if self.scenes.is_empty() {
+			ctx.request_quit();
+		}
+	}
+}
+
+impl ggez::event::EventHandler<ggez::GameError> for SceneStack {
+	fn mouse_motion_event(
+		&mut self,
+		ctx: &mut Context,
+		x: f32,
+		y: f32,
+		_dx: f32,
+		_dy: f32,
+	) -> GameResult {
+		if self.scenes.is_empty() {
+			return Ok(());
+		}
+		let scene = self.scenes.last_mut().unwrap();
+		let transition = scene.mouse_motion_event(ctx, &mut self.shared, x, y)?;
+		self.apply(ctx, transition);
+		Ok(())
+	}
+
+	fn mouse_button_down_event(
+		&mut self,
+		ctx: &mut Context,
+		button: ggez::event::MouseButton,
+		x: f32,
+		y: f32,
+	) -> GameResult {
+		if self.scenes.is_empty() {
+			return Ok(());
+		}
+		let scene = self.scenes.last_mut().unwrap();
+		let transition = scene.mouse_button_down_event(ctx, &mut self.shared, button, x, y)?;
+		self.apply(ctx, transition);
+		Ok(())
+	}
+
+	fn mouse_button_up_event(
+		&mut self,
+		ctx: &mut Context,
+		button: ggez::event::MouseButton,
+		x: f32,
+		y: f32,
+	) -> GameResult {
+		if self.scenes.is_empty() {
+			return Ok(());
+		}
+		let scene = self.scenes.last_mut().unwrap();
+		let transition = scene.mouse_button_up_event(ctx, &mut self.shared, button, x, y)?;
+		self.apply(ctx, transition);
+		Ok(())
+	}
+
+	fn mouse_wheel_event(&mut self, ctx: &mut Context, x: f32, y: f32) -> GameResult {
+		if self.scenes.is_empty() {
+			return Ok(());
+		}
+		let scene = self.scenes.last_mut().unwrap();
+		let transition = scene.mouse_wheel_event(ctx, &mut self.shared, x, y)?;
+		self.apply(ctx, transition);
+		Ok(())
+	}
+
+	fn key_down_event(&mut self, ctx: &mut Context, input: KeyInput, _repeated: bool) -> GameResult {
+		if self.scenes.is_empty() {
+			return Ok(());
+		}
+		let scene = self.scenes.last_mut().unwrap();
+		let transition = scene.key_down_event(ctx, &mut self.shared, input)?;
+		self.apply(ctx, transition);
+		Ok(())
+	}
+
+	fn gamepad_button_down_event(
+		&mut self,
+		ctx: &mut Context,
+		button: ggez::event::Button,
+		_id: ggez::event::GamepadId,
+	) -> GameResult {
+		if self.scenes.is_empty() {
+			return Ok(());
+		}
+		let Some(command) = Command::from_gamepad_button(button) else {
+			return Ok(());
+		};
+		let scene = self.scenes.last_mut().unwrap();
+		let transition = scene.command_event(ctx, &mut self.shared, command)?;
+		self.apply(ctx, transition);
+		Ok(())
+	}
+
+	fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+		for scene in self.scenes.iter_mut() {
+			scene.resize_event(ctx, width, height)?;
+		}
+		Ok(())
+	}
+
+	fn update(&mut self, ctx: &mut Context) -> GameResult {
+		if self.scenes.is_empty() {
+			return Ok(());
+		}
+		self.shared.toasts.update(ctx.time.delta());
+		if let Some(hot_reload) = self.hot_reload.as_mut() {
+			hot_reload.update(ctx, &mut self.shared, ctx.time.delta());
+		}
+		// Only the bottom scene (like a battle) is timed with the game clock, and the game is
+		// paused while any scene (like the pause menu or the settings) is on top of it.
+		self.shared.clock.set_paused(self.scenes.len() > 1);
+		let scene = self.scenes.last_mut().unwrap();
+		self.shared.clock.tick(ctx.time.delta(), self.shared.settings.animation_speed);
+		let transition = scene.update(ctx, &mut self.shared)?;
+		self.apply(ctx, transition);
+		let music = self.scenes.iter().rev().find_map(|scene| scene.music());
+		self.shared.audio.set_music(music);
+		self.shared.audio.update(ctx.time.delta(), &self.shared.settings)?;
+		Ok(())
+	}
+
+	fn draw(&mut self, ctx: &mut Context) -> GameResult {
+		if self.scenes.is_empty() {
+			return Ok(());
+		}
+		// We draw from the topmost non-overlay scene, as it hides everything below.
+		let bottom = self.scenes.iter().rposition(|scene| !scene.is_overlay()).unwrap_or(0);
+		let mut canvas = Canvas::from_frame(ctx, self.scenes[bottom].background_color());
+		for scene in self.scenes[bottom..].iter_mut() {
+			if scene.is_overlay() {
+				let (width, height) = ctx.gfx.size();
+				let darkening = Mesh::new_rectangle(
+					ctx,
+					DrawMode::fill(),
+					Rect::new(0.0, 0.0, width, height),
+					Color::from([0.0, 0.0, 0.0, 0.6]),
+				)?;
+				canvas.draw(&darkening, Vec2::new(0.0, 0.0));
+			}
+			scene.draw(ctx, &mut canvas, &self.shared)?;
+		}
+		self.shared.toasts.draw(ctx, &mut canvas)?;
+		canvas.finish(ctx)?;
+		Ok(())
+	}
+}
@@ -0,0 +1,115 @@
+//! What players can change to make the game work the way they like.
+//! The settings are saved in a small text file in the user config directory,
+//! with one `name = value` setting per line.
+
+use std::io::{Read, Write};
+
+use ggez::{Context, GameResult};
+
+use crate::clock::ClockSpeed;
+use crate::input::{key_from_name, key_name, Command, KeyBindings};
+
+const SETTINGS_FILE: &str = "/settings.txt";
+
+pub struct Settings {
+	/// Scrolling the battlefield line by moving the cursor near the left or right edge.
+	pub edge_panning: bool,
+	pub key_bindings: KeyBindings,
+	/// How fast the game clock goes, and thus the animations.
+	pub animation_speed: ClockSpeed,
+	/// Volumes in percent, the music and sound effects volumes are relative to the master one.
+	pub master_volume: u32,
+	pub music_volume: u32,
+	pub sfx_volume: u32,
+}
+
+impl Default for Settings {
+	fn default() -> Settings {
+		Settings {
+			edge_panning: true,
+			key_bindings: KeyBindings::default(),
+			animation_speed: ClockSpeed::Normal,
+			master_volume: 80,
+			music_volume: 60,
+			sfx_volume: 100,
+		}
+	}
+}
+
+impl Settings {
+	/// Loads the saved settings, or the default ones if there are none.
+	/// Lines that make no sense are ignored.
+	pub fn load(ctx: &Context) -> Settings {
+		let mut settings = Settings::default();
+		let mut content = String::new();
+		let read = ctx
+			.fs
+			.open(SETTINGS_FILE)
+			.ok()
+			.and_then(|mut file| file.read_to_string(&mut content).ok());
+		if read.is_none() {
+			return settings;
+		}
+		let mut bindings_were_saved = false;
+		for line in content.lines() {
+			let Some((name, value)) = line.split_once('=') else {
+				continue;
+			};
+			let (name, value) = (name.trim(), value.trim());
+			if name == "edge_panning" {
+				settings.edge_panning = value == "on";
+			} else if name == "animation_speed" {
+				if let Some(speed) = ClockSpeed::from_name(value) {
+					settings.animation_speed = speed;
+				}
+			} else if let Some(volume) = volume_setting(&mut settings, name) {
+				if let Ok(value) = value.parse::<u32>() {
+					*volume = value.min(100);
+				}
+			} else if let Some(command) = name.strip_prefix("key.").and_then(Command::from_id) {
+				if !bindings_were_saved {
+					settings.key_bindings.clear();
+					bindings_were_saved = true;
+				}
+				if let Some(key) = key_from_name(value) {
+					settings.key_bindings.add(command, key);
+				}
+			}
+		}
+		settings
+	}
+
+	pub fn save(&self, ctx: &Context) -> GameResult {
+		let mut content = String::new();
+		let on_off = if self.edge_panning { "on" } else { "off" };
+		content.push_str(&format!("edge_panning = {on_off}\n"));
+		content.push_str(&format!("animation_speed = {}\n", self.animation_speed.name()));
+		content.push_str(&format!("master_volume = {}\n", self.master_volume));
+		content.push_str(&format!("music_volume = {}\n", self.music_volume));
+		content.push_str(&format!("sfx_volume = {}\n", self.sfx_volume));
+		for (command, key) in self.key_bindings.iter() {
+			content.push_str(&format!("key.{} = {}\n", command.id(), key_name(key)));
+		}
+		let mut file = ctx.fs.create(SETTINGS_FILE)?;
+		file.write_all(content.as_bytes())?;
+		Ok(())
+	}
+
+	/// The next volume when cycling through them (in steps of 10%, back to 0 after 100%).
+	pub fn next_volume(volume: u32) -> u32 {
+		if volume >= 100 {
+			0
+		} else {
+			(volume / 10 + 1) * 10
+		}
+	}
+}
+
+fn volume_setting<'a>(settings: &'a mut Settings, name: &str) -> Option<&'a mut u32> {
+	match name {
+		"master_volume" => Some(&mut settings.master_volume),
+		"music_volume" => Some(&mut settings.music_volume),
+		"sfx_volume" => Some(&mut settings.sfx_volume),
+		_ => None,
+	}
+}
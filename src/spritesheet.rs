@@ -0,0 +1,333 @@
+//! The spritesheet, and its atlas (`assets/spritesheet.atlas`) that tells where each sprite
+//! is on the sheet. Sprites are looked up by name, so that the sheet can be rearranged
+//! without touching the code.
+//!
+//! Creatures can also have a sprite for each pose (`fwog.idle`, `fwog.attack`, etc.),
+//! possibly animated with a few frames. The ones that do not are still animated a bit
+//! (bobbing when idle, jumping forward when attacking and shaking when hurt).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+use std::rc::Rc;
+use std::time::Duration;
+
+use ggez::glam::*;
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Image, Mesh, Rect, Text};
+use ggez::{Context, GameError, GameResult};
+
+/// A sprite of the sheet, as described in the atlas.
+#[derive(Clone, Copy)]
+pub struct Sprite {
+	/// Where the sprite is, in fractions of the size of the sheet.
+	pub rect: Rect,
+	/// The point of the sprite (in fractions of its size) that is drawn where the sprite
+	/// is placed, and around which it rotates.
+	pub pivot: Vec2,
+	/// How big the sprite is drawn by default.
+	pub scale: f32,
+	/// The frames of an animated sprite are side by side on the sheet, starting with `rect`
+	/// and going to the right, and they are played in a loop.
+	pub frames: u32,
+	pub fps: f32,
+}
+
+impl Sprite {
+	/// Where the frame to display is on the sheet, at the given time of the animation.
+	fn frame_rect(&self, time: Duration) -> Rect {
+		let frame = (time.as_secs_f32() * self.fps) as u32 % self.frames.max(1);
+		let mut rect = self.rect;
+		rect.translate(Vec2::new(frame as f32 * rect.w, 0.0));
+		rect
+	}
+}
+
+/// What a creature is doing, each pose can have its own (animated) sprite.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Pose {
+	Idle,
+	/// While the creature attacks.
+	Attack,
+	/// For a moment after the creature got hit.
+	Hurt,
+}
+
+impl Pose {
+	/// The suffix of the sprite names of this pose in the atlas.
+	fn name(self) -> &'static str {
+		match self {
+			Pose::Idle => "idle",
+			Pose::Attack => "attack",
+			Pose::Hurt => "hurt",
+		}
+	}
+}
+
+/// How a sprite is moved, scaled and tinted to show a pose it has no sprite for
+/// (or to keep a still idle sprite alive), at the given time of the animation.
+/// The phase (in turns) keeps creatures from all bobbing together.
+fn pose_motion(pose: Pose, time: Duration, phase: f32) -> (Vec2, f32, Color) {
+	let time = time.as_secs_f32();
+	match pose {
+		Pose::Idle => {
+			let bob = (time * 0.8 + phase).fract();
+			(Vec2::new(0.0, -4.0 * (bob * TAU).sin().abs()), 1.0, Color::WHITE)
+		},
+		Pose::Attack => (Vec2::new(0.0, -10.0), 1.12, Color::WHITE),
+		Pose::Hurt => {
+			let shake = 4.0 * (time * 40.0 * TAU).sin();
+			(Vec2::new(shake, 0.0), 0.92, Color::new(1.0, 0.55, 0.55, 1.0))
+		},
+	}
+}
+
+/// Parses the atlas, with one `name = x y w h, pivot px py, scale s, frames n, fps f`
+/// sprite per line (see the comments in the atlas file itself).
+fn parse_atlas(text: &str) -> Result<HashMap<String, Sprite>, String> {
+	let mut sprites = HashMap::new();
+	for (line_index, line) in text.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		let error = |what: &str| format!("line {}: {what}", line_index + 1);
+		let numbers = |text: &str, count: usize| -> Result<Vec<f32>, String> {
+			let numbers: Vec<f32> = text
+				.split_whitespace()
+				.map(|number| number.parse())
+				.collect::<Result<_, _>>()
+				.map_err(|_| error(&format!("\"{text}\" is not made of numbers")))?;
+			if numbers.len() != count {
+				return Err(error(&format!("expected {count} numbers in \"{text}\"")));
+			}
+			Ok(numbers)
+		};
+		let Some((name, description)) = line.split_once('=') else {
+			return Err(error("expected \"name = x y w h\""));
+		};
+		let mut parts = description.split(',');
+		let rect = numbers(parts.next().unwrap_or(""), 4)?;
+		let mut sprite = Sprite {
+			rect: Rect::new(rect[0], rect[1], rect[2], rect[3]),
+			pivot: Vec2::new(0.5, 0.5),
+			scale: 1.0,
+			frames: 1,
+			fps: 0.0,
+		};
+		for part in parts {
+			let part = part.trim();
+			if let Some(pivot) = part.strip_prefix("pivot") {
+				let pivot = numbers(pivot, 2)?;
+				sprite.pivot = Vec2::new(pivot[0], pivot[1]);
+			} else if let Some(scale) = part.strip_prefix("scale") {
+				sprite.scale = numbers(scale, 1)?[0];
+			} else if let Some(frames) = part.strip_prefix("frames") {
+				sprite.frames = numbers(frames, 1)?[0].max(1.0) as u32;
+			} else if let Some(fps) = part.strip_prefix("fps") {
+				sprite.fps = numbers(fps, 1)?[0];
+			} else {
+				return Err(error(&format!("unknown property \"{part}\"")));
+			}
+		}
+		sprites.insert(name.trim().to_string(), sprite);
+	}
+	Ok(sprites)
+}
+
+/// The image of all the sprites, with where each sprite is in it. Hot reloading replaces
+/// the image or the atlas in place, so every scene draws the new sprites right away.
+#[derive(Clone)]
+pub struct Spritesheet {
+	image: Rc<RefCell<Image>>,
+	sprites: Rc<RefCell<HashMap<String, Sprite>>>,
+}
+
+impl Spritesheet {
+	/// The size (in pixels, at a scale of 1) of the placeholders drawn for missing sprites,
+	/// about the size of a creature on a card.
+	const PLACEHOLDER_SIZE: f32 = 100.0;
+
+	/// The spritesheet and atlas that come with the game.
+	pub fn load(ctx: &Context) -> GameResult<Spritesheet> {
+		let image = Image::from_bytes(ctx, include_bytes!("../assets/spritesheet.png"))?;
+		let sprites = parse_atlas(include_str!("../assets/spritesheet.atlas")).map_err(|error| {
+			GameError::ResourceLoadError(format!("spritesheet.atlas, {error}"))
+		})?;
+		let image = Rc::new(RefCell::new(image));
+		Ok(Spritesheet { image, sprites: Rc::new(RefCell::new(sprites)) })
+	}
+
+	/// Replaces the image, the atlas stays the same.
+	pub fn reload_image(&self, ctx: &Context, bytes: &[u8]) -> GameResult {
+		*self.image.borrow_mut() = Image::from_bytes(ctx, bytes)?;
+		Ok(())
+	}
+
+	/// Replaces the atlas, unless it does not parse.
+	pub fn reload_atlas(&self, text: &str) -> Result<(), String> {
+		*self.sprites.borrow_mut() = parse_atlas(text)?;
+		Ok(())
+	}
+
+	pub fn sprite(&self, name: &str) -> Option<Sprite> {
+		self.sprites.borrow().get(name).copied()
+	}
+
+	/// Is there a sprite for the creature (in any pose)?
+	pub fn has_creature_sprite(&self, name: &str) -> bool {
+		self.posed_sprite(name, Pose::Idle).is_some()
+	}
+
+	/// The sprite of a creature in the given pose, falling back to its idle pose
+	/// and then to its single sprite (for creatures that have no poses).
+	fn posed_sprite(&self, name: &str, pose: Pose) -> Option<Sprite> {
+		self.sprite(&format!("{name}.{}", pose.name()))
+			.or_else(|| self.sprite(&format!("{name}.{}", Pose::Idle.name())))
+			.or_else(|| self.sprite(name))
+	}
+
+	/// The size of the sprite in pixels, when drawn at a scale of 1.
+	fn pixel_size(&self, sprite: &Sprite) -> Vec2 {
+		let image = self.image.borrow();
+		let (width, height) = (image.width() as f32, image.height() as f32);
+		Vec2::new(sprite.rect.w * width, sprite.rect.h * height)
+	}
+
+	/// Draws the sprite with its pivot at `dest`, at its default scale times `scale`.
+	/// Sprites that are not in the atlas are drawn as placeholders.
+	pub fn draw(
+		&self,
+		ctx: &mut Context,
+		canvas: &mut Canvas,
+		name: &str,
+		dest: Vec2,
+		scale: f32,
+		rotation: f32,
+	) -> GameResult {
+		let Some(sprite) = self.sprite(name) else {
+			return draw_placeholder(ctx, canvas, dest, Spritesheet::PLACEHOLDER_SIZE * scale);
+		};
+		let scale = sprite.scale * scale;
+		canvas.draw(
+			&*self.image.borrow(),
+			DrawParam::default()
+				.dest(dest)
+				.offset(sprite.pivot)
+				.rotation(rotation)
+				.scale(Vec2::new(scale, scale))
+				.src(sprite.frame_rect(Duration::ZERO)),
+		);
+		Ok(())
+	}
+
+	/// Draws a creature in the given pose (see `posed_sprite`) with its pivot at `dest`,
+	/// `time` being how far its animation is (usually the game clock time).
+	/// Poses without their own sprite, and still idle sprites, get `pose_motion`.
+	pub fn draw_posed(
+		&self,
+		ctx: &mut Context,
+		canvas: &mut Canvas,
+		name: &str,
+		pose: Pose,
+		time: Duration,
+		dest: Vec2,
+	) -> GameResult {
+		let Some(sprite) = self.posed_sprite(name, pose) else {
+			return draw_placeholder(ctx, canvas, dest, Spritesheet::PLACEHOLDER_SIZE);
+		};
+		let has_pose_sprite = self.sprite(&format!("{name}.{}", pose.name())).is_some();
+		let needs_motion = match pose {
+			Pose::Idle => sprite.frames <= 1,
+			Pose::Attack | Pose::Hurt => !has_pose_sprite,
+		};
+		let (offset, scale, color) = if needs_motion {
+			let phase = name.bytes().map(f32::from).sum::<f32>() * 0.137;
+			pose_motion(pose, time, phase)
+		} else {
+			(Vec2::ZERO, 1.0, Color::WHITE)
+		};
+		canvas.draw(
+			&*self.image.borrow(),
+			DrawParam::default()
+				.dest(dest + offset)
+				.offset(sprite.pivot)
+				.scale(Vec2::splat(sprite.scale * scale))
+				.src(sprite.frame_rect(time))
+				.color(color),
+		);
+		Ok(())
+	}
+
+	/// Draws the sprite centered on `center` and scaled to fit in a square of the given size
+	/// (ignoring its pivot and default scale), for icons.
+	pub fn draw_fit(
+		&self,
+		ctx: &mut Context,
+		canvas: &mut Canvas,
+		name: &str,
+		center: Vec2,
+		size: f32,
+	) -> GameResult {
+		let Some(sprite) = self.sprite(name) else {
+			return draw_placeholder(ctx, canvas, center, size);
+		};
+		let scale = size / self.pixel_size(&sprite).max_element();
+		canvas.draw(
+			&*self.image.borrow(),
+			DrawParam::default()
+				.dest(center)
+				.offset(Vec2::new(0.5, 0.5))
+				.scale(Vec2::new(scale, scale))
+				.src(sprite.frame_rect(Duration::ZERO)),
+		);
+		Ok(())
+	}
+}
+
+/// A loud magenta square with a question mark, that is hard to miss.
+fn draw_placeholder(ctx: &mut Context, canvas: &mut Canvas, center: Vec2, size: f32) -> GameResult {
+	let rect = Rect::new(center.x - size / 2.0, center.y - size / 2.0, size, size);
+	let square = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, Color::MAGENTA)?;
+	canvas.draw(&square, Vec2::new(0.0, 0.0));
+	let mut text = Text::new("?");
+	text.set_scale(size * 0.8);
+	let text_dims = Vec2::from(text.measure(ctx)?);
+	canvas.draw(&text, DrawParam::from(center - text_dims / 2.0).color(Color::BLACK));
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_sprites_with_defaults() {
+		let sprites =
+			parse_atlas("# a comment\n\nfwog = 0 0 10 20\nfwog.idle = 10 0 10 20, frames 2, fps 4")
+				.unwrap();
+		let fwog = &sprites["fwog"];
+		assert_eq!(fwog.rect, Rect::new(0.0, 0.0, 10.0, 20.0));
+		assert_eq!(fwog.pivot, Vec2::new(0.5, 0.5));
+		assert_eq!((fwog.scale, fwog.frames), (1.0, 1));
+		let idle = &sprites["fwog.idle"];
+		assert_eq!((idle.frames, idle.fps), (2, 4.0));
+	}
+
+	#[test]
+	fn the_embedded_atlas_parses() {
+		assert!(parse_atlas(include_str!("../assets/spritesheet.atlas")).is_ok());
+	}
+
+	#[test]
+	fn reports_errors_with_their_line() {
+		for (atlas, error) in [
+			("fwog 0 0 10 20", "line 1: expected \"name = x y w h\""),
+			("\nfwog = 0 0 10", "line 2: expected 4 numbers in \" 0 0 10\""),
+			("fwog = 0 0 a 20", "line 1: \" 0 0 a 20\" is not made of numbers"),
+			("fwog = 0 0 10 20, pivot 1", "line 1: expected 2 numbers in \" 1\""),
+			("fwog = 0 0 10 20, shiny", "line 1: unknown property \"shiny\""),
+		] {
+			assert_eq!(parse_atlas(atlas).err().as_deref(), Some(error));
+		}
+	}
+}
@@ -0,0 +1,78 @@
+//! Toasts, short messages that pop up at the top of the screen over every scene
+//! (like errors when reloading assets in dev mode) and go away after a few seconds.
+
+use std::time::Duration;
+
+use ggez::glam::*;
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, Rect, Text};
+use ggez::{Context, GameResult};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ToastKind {
+	Info,
+	Error,
+}
+
+struct Toast {
+	kind: ToastKind,
+	text: String,
+	/// Toasts are timed with the wall clock, as they must not wait for the game to be unpaused.
+	time_left: Duration,
+}
+
+#[derive(Default)]
+pub struct Toasts {
+	toasts: Vec<Toast>,
+}
+
+impl Toasts {
+	const MAX_TOASTS: usize = 5;
+	const WIDTH: f32 = 600.0;
+	const TEXT_SIZE: f32 = 18.0;
+
+	pub fn push(&mut self, kind: ToastKind, text: String) {
+		// Errors stay longer, they are usually longer to read.
+		let time_left = match kind {
+			ToastKind::Info => Duration::from_secs(2),
+			ToastKind::Error => Duration::from_secs(8),
+		};
+		self.toasts.push(Toast { kind, text, time_left });
+		if self.toasts.len() > Toasts::MAX_TOASTS {
+			self.toasts.remove(0);
+		}
+	}
+
+	pub fn update(&mut self, wall_delta: Duration) {
+		for toast in self.toasts.iter_mut() {
+			toast.time_left = toast.time_left.saturating_sub(wall_delta);
+		}
+		self.toasts.retain(|toast| !toast.time_left.is_zero());
+	}
+
+	/// Draws the toasts centered at the top of the screen, the most recent one at the bottom.
+	pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+		let (screen_width, _screen_height) = ctx.gfx.size();
+		let mut y = 10.0;
+		for toast in self.toasts.iter() {
+			let mut text = Text::new(toast.text.as_str());
+			text.set_scale(Toasts::TEXT_SIZE).set_bounds(Vec2::new(Toasts::WIDTH, f32::INFINITY));
+			let dims = Vec2::from(text.measure(ctx)?);
+			let rect = Rect::new(
+				(screen_width - dims.x) / 2.0 - 10.0,
+				y,
+				dims.x + 20.0,
+				dims.y + 12.0,
+			);
+			let background_color = match toast.kind {
+				ToastKind::Info => Color::from_rgb(40, 70, 50),
+				ToastKind::Error => Color::from_rgb(120, 30, 40),
+			};
+			let background = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, background_color)?;
+			canvas.draw(&background, Vec2::new(0.0, 0.0));
+			let pos = Vec2::new(rect.left() + 10.0, rect.top() + 6.0);
+			canvas.draw(&text, DrawParam::from(pos).color(Color::WHITE));
+			y += rect.h + 6.0;
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,120 @@
+//! Tweens, values that go from a start value to an end value over the course of
+//! an animation, following an easing curve (so that things can slow down on arrival,
+//! overshoot a bit, or bounce, instead of moving mechanically).
+
+use ggez::glam::Vec2;
+
+#[derive(Clone, Copy)]
+pub enum Easing {
+	Linear,
+	/// Fast at first, then slows down on arrival.
+	EaseOut,
+	/// Goes a bit past the end value before coming back to it.
+	Back,
+	/// Bounces on the end value a few times, like something falling on the floor.
+	Bounce,
+}
+
+impl Easing {
+	/// Maps the progression of an animation to how far the tweened value is from
+	/// its start value to its end value. The progression is clamped between 0 and 1,
+	/// the result is 0 at the start and 1 at the end (but can go past 1 in between).
+	pub fn apply(self, progression: f32) -> f32 {
+		let t = progression.clamp(0.0, 1.0);
+		match self {
+			Easing::Linear => t,
+			Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+			Easing::Back => {
+				let overshoot = 1.70158;
+				1.0 + (overshoot + 1.0) * (t - 1.0).powi(3) + overshoot * (t - 1.0).powi(2)
+			},
+			Easing::Bounce => {
+				let (n, d) = (7.5625, 2.75);
+				if t < 1.0 / d {
+					n * t * t
+				} else if t < 2.0 / d {
+					let t = t - 1.5 / d;
+					n * t * t + 0.75
+				} else if t < 2.5 / d {
+					let t = t - 2.25 / d;
+					n * t * t + 0.9375
+				} else {
+					let t = t - 2.625 / d;
+					n * t * t + 0.984375
+				}
+			},
+		}
+	}
+}
+
+/// What can be tweened (positions, scales, rotations, alphas, etc.).
+pub trait Lerp: Copy {
+	/// The value that is `t` of the way from `self` to `end`.
+	fn lerp(self, end: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+	fn lerp(self, end: f32, t: f32) -> f32 {
+		self + t * (end - self)
+	}
+}
+
+impl Lerp for Vec2 {
+	fn lerp(self, end: Vec2, t: f32) -> Vec2 {
+		self + t * (end - self)
+	}
+}
+
+#[derive(Clone, Copy)]
+pub struct Tween<T: Lerp> {
+	pub start: T,
+	pub end: T,
+	pub easing: Easing,
+}
+
+impl<T: Lerp> Tween<T> {
+	pub fn new(start: T, end: T, easing: Easing) -> Tween<T> {
+		Tween { start, end, easing }
+	}
+
+	/// The tweened value at the given progression of the animation.
+	pub fn at(&self, progression: f32) -> T {
+		self.start.lerp(self.end, self.easing.apply(progression))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const EASINGS: [Easing; 4] = [Easing::Linear, Easing::EaseOut, Easing::Back, Easing::Bounce];
+
+	#[test]
+	fn easings_start_at_0_and_end_at_1() {
+		for easing in EASINGS {
+			assert!(easing.apply(0.0).abs() < 1e-5);
+			assert!((easing.apply(1.0) - 1.0).abs() < 1e-5);
+		}
+	}
+
+	#[test]
+	fn progression_is_clamped() {
+		for easing in EASINGS {
+			assert_eq!(easing.apply(-1.0), easing.apply(0.0));
+			assert_eq!(easing.apply(2.0), easing.apply(1.0));
+		}
+	}
+
+	#[test]
+	fn back_overshoots() {
+		assert!(Easing::Back.apply(0.8) > 1.0);
+	}
+
+	#[test]
+	fn tween_goes_from_start_to_end() {
+		let tween = Tween::new(10.0, 20.0, Easing::Linear);
+		assert_eq!(tween.at(0.0), 10.0);
+		assert_eq!(tween.at(0.5), 15.0);
+		assert_eq!(tween.at(1.0), 20.0);
+	}
+}
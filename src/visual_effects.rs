@@ -0,0 +1,195 @@
+//! Short-lived visual effects (damage numbers, shakes, particles, etc.) that show what
+//! just happened on the battlefield. Unlike animations, they do not block the inputs,
+//! they are just drawn on top of everything until they are over.
+
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use ggez::glam::*;
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, MeshBuilder, Rect, Text};
+use ggez::{Context, GameResult};
+
+use crate::clock::GameClock;
+use crate::spritesheet::Pose;
+use crate::tween::{Easing, Tween};
+use crate::{CreatureId, TimeProgression};
+
+enum VisualEffectWhat {
+	/// A number (or a word) that floats up and fades away.
+	Popup { text: String, color: Color, pos: Vec2 },
+	/// The creature shakes and flashes red for a moment, in its hurt pose.
+	Hit { creature: CreatureId },
+	/// The creature is in its attack pose for a moment.
+	Attack { creature: CreatureId },
+	/// A burst of dust where a creature was, when it flees.
+	Poof { center: Vec2 },
+	/// Little hearts floating up from a foe that becomes a friend.
+	Hearts { center: Vec2 },
+}
+
+struct VisualEffect {
+	tp: TimeProgression,
+	what: VisualEffectWhat,
+}
+
+pub struct VisualEffects {
+	clock: GameClock,
+	effects: Vec<VisualEffect>,
+}
+
+impl VisualEffects {
+	const POOF_PARTICLES: usize = 10;
+	const HEARTS: usize = 5;
+
+	pub fn new(clock: &GameClock) -> VisualEffects {
+		VisualEffects { clock: clock.clone(), effects: vec![] }
+	}
+
+	fn add(&mut self, duration_secs: f32, what: VisualEffectWhat) {
+		let tp = TimeProgression::with_duration(&self.clock, Duration::from_secs_f32(duration_secs));
+		self.effects.push(VisualEffect { tp, what });
+	}
+
+	pub fn popup(&mut self, text: String, color: Color, pos: Vec2) {
+		self.add(0.9, VisualEffectWhat::Popup { text, color, pos });
+	}
+
+	pub fn hit(&mut self, creature: CreatureId) {
+		self.add(0.35, VisualEffectWhat::Hit { creature });
+	}
+
+	pub fn attack(&mut self, creature: CreatureId) {
+		self.add(0.35, VisualEffectWhat::Attack { creature });
+	}
+
+	pub fn poof(&mut self, center: Vec2) {
+		self.add(0.6, VisualEffectWhat::Poof { center });
+	}
+
+	pub fn hearts(&mut self, center: Vec2) {
+		self.add(1.2, VisualEffectWhat::Hearts { center });
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.effects.is_empty()
+	}
+
+	pub fn remove_finished(&mut self) {
+		self.effects.retain(|effect| effect.tp.progression() < 1.0);
+	}
+
+	/// How much the given creature is moved by its shaking (if it was hit recently).
+	pub fn shake_offset(&self, creature: CreatureId, scale: f32) -> Vec2 {
+		self.effects
+			.iter()
+			.filter(|effect| {
+				matches!(effect.what, VisualEffectWhat::Hit { creature: hit } if hit == creature)
+			})
+			.map(|effect| {
+				let progression = effect.tp.progression();
+				let amplitude = Tween::new(8.0 * scale, 0.0, Easing::EaseOut).at(progression);
+				Vec2::new((progression * 40.0).sin() * amplitude, 0.0)
+			})
+			.sum()
+	}
+
+	/// The pose the given creature should be drawn in, getting hit being more important
+	/// to show than attacking.
+	pub fn pose(&self, creature: CreatureId) -> Pose {
+		let mut pose = Pose::Idle;
+		for effect in self.effects.iter() {
+			match effect.what {
+				VisualEffectWhat::Hit { creature: hit } if hit == creature => return Pose::Hurt,
+				VisualEffectWhat::Attack { creature: attacker } if attacker == creature => {
+					pose = Pose::Attack;
+				},
+				_ => {},
+			}
+		}
+		pose
+	}
+
+	/// Draws all the effects, `creature_rect` gives where a creature is on the screen
+	/// (if it is still on the battlefield).
+	pub fn draw(
+		&self,
+		ctx: &mut Context,
+		canvas: &mut Canvas,
+		scale: f32,
+		creature_rect: impl Fn(CreatureId) -> Option<Rect>,
+	) -> GameResult {
+		for effect in self.effects.iter() {
+			let progression = effect.tp.progression();
+			let alpha = Tween::new(1.0, 0.0, Easing::Linear).at(progression);
+			match &effect.what {
+				VisualEffectWhat::Popup { text, color, pos } => {
+					let rise = Tween::new(0.0, 50.0 * scale, Easing::EaseOut).at(progression);
+					let mut text = Text::new(text.as_str());
+					text.set_scale(30.0 * scale);
+					let width = Vec2::from(text.measure(ctx)?).x;
+					let pos = *pos - Vec2::new(width / 2.0, rise);
+					let color = Color { a: alpha, ..*color };
+					canvas.draw(&text, DrawParam::from(pos).color(color));
+				},
+				VisualEffectWhat::Hit { creature } => {
+					let Some(rect) = creature_rect(*creature) else {
+						continue;
+					};
+					let flash_color = Color::new(1.0, 0.2, 0.2, 0.5 * alpha);
+					let flash = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, flash_color)?;
+					canvas.draw(&flash, Vec2::new(0.0, 0.0));
+				},
+				VisualEffectWhat::Attack { .. } => {},
+				VisualEffectWhat::Poof { center } => {
+					let distance = Tween::new(0.0, 70.0 * scale, Easing::EaseOut).at(progression);
+					let radius = Tween::new(14.0 * scale, 3.0 * scale, Easing::Linear).at(progression);
+					let color = Color::new(0.85, 0.85, 0.85, alpha);
+					for i in 0..VisualEffects::POOF_PARTICLES {
+						let angle = i as f32 / VisualEffects::POOF_PARTICLES as f32 * TAU;
+						let pos = *center + Vec2::from_angle(angle) * distance;
+						let particle = Mesh::new_circle(ctx, DrawMode::fill(), pos, radius, 0.5, color)?;
+						canvas.draw(&particle, Vec2::new(0.0, 0.0));
+					}
+				},
+				VisualEffectWhat::Hearts { center } => {
+					let rise = Tween::new(0.0, 90.0 * scale, Easing::EaseOut).at(progression);
+					for i in 0..VisualEffects::HEARTS {
+						let spread = (i as f32 - (VisualEffects::HEARTS - 1) as f32 / 2.0) * 30.0;
+						// Each heart wobbles a bit on its way up, not in sync with the others.
+						let wobble = (progression * 12.0 + i as f32).sin() * 6.0;
+						let offset = Vec2::new(spread + wobble, -((i % 2) as f32) * 15.0) * scale;
+						let pos = *center + offset - Vec2::new(0.0, rise);
+						draw_heart(ctx, canvas, pos, 12.0 * scale, Color::new(1.0, 0.4, 0.6, alpha))?;
+					}
+				},
+			}
+		}
+		Ok(())
+	}
+}
+
+/// A heart made of two circles and a triangle, `size` is about its half width.
+fn draw_heart(
+	ctx: &mut Context,
+	canvas: &mut Canvas,
+	center: Vec2,
+	size: f32,
+	color: Color,
+) -> GameResult {
+	let lobe_radius = size / 2.0;
+	let mut builder = MeshBuilder::new();
+	builder
+		.circle(DrawMode::fill(), center + Vec2::new(-lobe_radius, 0.0), lobe_radius, 0.2, color)?
+		.circle(DrawMode::fill(), center + Vec2::new(lobe_radius, 0.0), lobe_radius, 0.2, color)?
+		.triangles(
+			&[
+				center + Vec2::new(-size, lobe_radius * 0.3),
+				center + Vec2::new(size, lobe_radius * 0.3),
+				center + Vec2::new(0.0, size * 1.2),
+			],
+			color,
+		)?;
+	let heart = Mesh::from_data(ctx, builder.build());
+	canvas.draw(&heart, Vec2::new(0.0, 0.0));
+	Ok(())
+}